@@ -2,7 +2,8 @@
 // #[cfg(not(target_arch = "wasm32"))]
 // use rfd::FileDialog;
 
-use std::{sync::{Arc, Mutex}, thread, time::Duration};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 #[cfg(target_arch = "wasm32")]
 use {
@@ -13,51 +14,159 @@ use {
     js_sys::Uint8Array,
 };
 
-use crate::{audio::{interface::AudioBackend, PlatformAudioHandler}, show_file_label_with_click};
-use crate::shortcut::{interface::ShortcutListener, PlatformShortcutListener};
+use crate::{audio::{interface::{AudioBackend, AudioEvent}, AudioBackendKind, PlatformAudioHandler}, show_file_label_with_click};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::audio::SUPPORTED_EXTENSIONS;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::shortcut::trigger::{spawn_trigger_loop, TriggerInputs};
+#[cfg(target_arch = "wasm32")]
+use crate::shortcut::trigger::WebTriggers;
+use crate::shortcut::trigger::{Binding, BoardBindings, GamepadBinding, MidiBinding, SharedSnapshot, TriggerAction, TriggerSnapshot};
+use crate::gamepad::PlatformGamepadInput;
+#[cfg(target_arch = "wasm32")]
+use crate::midi::feedback::{pad_values, FileCache};
+use crate::midi::{PlatformMidiInput, PlatformMidiOutput};
+use crate::shortcut::action::AppAction;
+use crate::shortcut::conflict::{find_conflicts, Conflict, ConflictKind, ShortcutOwner};
 use crate::shortcut::keycodes::SerializableKeycode;
+use crate::shortcut::layout::KeyboardLayout;
 use crate::widgets::shortcut::PlatformShortcutRecorder;
 use crate::widgets::settings::SettingsWindow;
-use crate::sound::Sound;
-use crate::utils::*;
+use crate::widgets::generator::GeneratorEditor;
+use crate::widgets::learn::InputLearn;
+use crate::sound::{RetriggerPolicy, Sound, TriggerMode};
+use crate::board::{archive, Board, Boards};
+#[cfg(target_arch = "wasm32")]
+use crate::utils::{download_file, trigger_board_picker, trigger_file_picker};
 
 use crate::icons::*;
 
 #[derive(serde::Deserialize, serde::Serialize)]
 #[serde(default)]
 pub struct Noisette {
+    /// The sounds of the active board.
     sounds: Arc<Mutex<Vec<Sound>>>,
+    boards: Boards,
 
     #[serde(skip)]
     audio: Arc<Mutex<PlatformAudioHandler>>,
     #[serde(skip)]
+    midi: Arc<Mutex<PlatformMidiInput>>,
+    /// Lit by the trigger loop on the desktop, by [`Noisette::update`] in the
+    /// browser.
+    #[serde(skip)]
+    midi_output: Arc<Mutex<PlatformMidiOutput>>,
+    /// Which sound files exist, for the pad colours.
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    pad_files: FileCache,
+    #[serde(skip)]
+    learning_midi: Option<usize>,
+    #[serde(skip)]
+    gamepad: PlatformGamepadInput,
+    #[serde(skip)]
+    learning_gamepad: Option<usize>,
+    #[serde(skip)]
     last_pressed_keys: Option<Vec<SerializableKeycode>>,
     #[serde(skip)]
-    shortcut_listener: Arc<Mutex<PlatformShortcutListener>>,
+    bindings: SharedSnapshot,
+    #[serde(skip)]
+    published: PublishedSnapshot,
+    /// Checks the shortcuts every frame, created once the page is up.
+    #[cfg(target_arch = "wasm32")]
+    #[serde(skip)]
+    web_triggers: Option<WebTriggers>,
+    #[serde(skip)]
+    last_error: Option<String>,
+    /// Shortcut just recorded and the older binding it conflicts with.
+    #[serde(skip)]
+    pending_conflict: Option<(ShortcutOwner, ShortcutOwner)>,
 
     settings: Arc<Mutex<SettingsWindow>>,
     dragging_index: Option<usize>,
     listening_shortcut: Option<usize>,
+    /// Whether the shortcuts name the physical key; false in the state saved
+    /// by older versions, see [`Noisette::migrate_physical_shortcuts`].
+    #[serde(default)]
+    physical_shortcuts: bool,
 }
 
 impl Default for Noisette {
     fn default() -> Self {
         Self {
             sounds: Arc::new(Mutex::new(Vec::new())),
+            boards: Boards::default(),
             settings: Arc::new(Mutex::new(SettingsWindow::default())),
             listening_shortcut: None,
             dragging_index: None,
             audio: Arc::new(Mutex::new(PlatformAudioHandler::new())),
-            shortcut_listener: Arc::new(Mutex::new(PlatformShortcutListener::new())),
+            midi: Arc::new(Mutex::new(PlatformMidiInput::new())),
+            midi_output: Arc::new(Mutex::new(PlatformMidiOutput::new())),
+            #[cfg(target_arch = "wasm32")]
+            pad_files: FileCache::new(),
+            learning_midi: None,
+            gamepad: PlatformGamepadInput::new(),
+            learning_gamepad: None,
+            bindings: SharedSnapshot::default(),
+            published: PublishedSnapshot::default(),
+            #[cfg(target_arch = "wasm32")]
+            web_triggers: None,
             last_pressed_keys: None,
+            last_error: None,
+            pending_conflict: None,
+            physical_shortcuts: true,
         }
     }
 }
 
 impl Noisette {
+    /// Older versions stored the key the layout produces: on Windows that is
+    /// a virtual key, now read by its position on the keyboard.
+    fn migrate_physical_shortcuts(&mut self) {
+        // Nel browser il `KeyboardEvent.code` indica gia' il tasto fisico
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.physical_shortcuts {
+            use crate::shortcut::desktop::physical_key;
+
+            let migrate = |keys: &mut Vec<SerializableKeycode>| {
+                for key in keys.iter_mut() {
+                    *key = physical_key(key.clone());
+                }
+            };
+            let migrate_sound = |sound: &mut Sound| {
+                sound.shortcut.iter_mut().for_each(migrate);
+                sound.sequence.iter_mut().for_each(migrate);
+            };
+
+            if let Ok(mut sounds) = self.sounds.lock() {
+                sounds.iter_mut().for_each(migrate_sound);
+            }
+            for board in self.boards.iter_mut() {
+                board.sounds.iter_mut().for_each(migrate_sound);
+            }
+
+            if let Ok(mut settings) = self.settings.lock() {
+                settings.record_shortcut.iter_mut().for_each(migrate);
+                for action in AppAction::ALL {
+                    settings.action_shortcuts.get_mut(action).iter_mut().for_each(migrate);
+                }
+            }
+        }
+
+        self.physical_shortcuts = true;
+    }
+
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let instance = if let Some(storage) = cc.storage {
-            let instance: Noisette = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        let mut instance = if let Some(storage) = cc.storage {
+            let mut instance: Noisette = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            instance.boards.repair();
+            instance.migrate_physical_shortcuts();
+
+            if let Ok(settings) = instance.settings.lock()
+            && settings.audio_backend != AudioBackendKind::Device
+            && let Ok(mut audio) = instance.audio.lock() {
+                audio.set_backend(settings.audio_backend);
+            }
 
             if let Ok(settings) = instance.settings.lock() && let Some(device_name) = settings.selected_device_name.clone() {
                 if let Ok(mut audio) = instance.audio.lock() {
@@ -70,70 +179,53 @@ impl Noisette {
             Noisette::default()
         };
 
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            let shortcut_listener = Arc::clone(&instance.shortcut_listener);
-            let sounds = Arc::clone(&instance.sounds);
-            let audio = Arc::clone(&instance.audio);
-            let settings = Arc::clone(&instance.settings);
-
-            thread::spawn(move || {
-                loop {
-                    if let (Ok(mut listener), Ok(mut sounds)) = (shortcut_listener.lock(), sounds.lock()) {
-                        listener.update();
-
-                        for idx in 0..sounds.len() {
-                            let sound = &mut sounds[idx];
-
-                            if let Some(shortcut) = &sound.shortcut 
-                            && let Ok(audio) = &mut audio.lock()  {
-                                if !listener.is_pressed(&shortcut) {
-                                    // Se non e' premuta la shortcut del suono non fare nulla
-                                    continue;
-                                };
+        if let Ok(mut audio) = instance.audio.lock() {
+            // Il thread audio chiede un repaint quando lo stato cambia
+            audio.set_repaint_context(cc.egui_ctx.clone());
 
-                                if sound.editing {
-                                    // Se il suono e' in modalita' modifica non fare nulla
-                                    continue;
-                                }
+            if let Ok(settings) = instance.settings.lock() {
+                audio.set_volume(settings.master_volume);
+            }
+        }
 
-                                if audio.is_playing(None) {
-                                    // Se c'e' gia' un suono in riproduzione
-                                    if let Ok(settings) = settings.lock() {
-                                        if settings.toggle_to_stop && audio.is_playing(Some(sound.clone())) {
-                                            // e c'e' l'opzione di premere una seconda volta per stoppare l'audio
-                                            // e l'audio attuale e' uguale a quello in riproduzione
-                                            audio.stop(sound);
-                                            thread::sleep(Duration::from_millis(1000));
-                                            continue;
-                                        }
+        if let Ok(mut midi) = instance.midi.lock() {
+            midi.set_repaint_context(cc.egui_ctx.clone());
 
-                                        if settings.stop_on_new  && !audio.is_playing(Some(sound.clone())) {
-                                            // e c'e' l'opzione di premere un altra shortcut per iniziare un altro audi
-                                            // e l'audio attuale e' diverso da quello in riproduzione
-                                            audio.stop_all();
-                                            audio.play(sound);
-                                            thread::sleep(Duration::from_millis(1000));
-                                            continue;
-                                        }
-                                    }
+            if let Ok(settings) = instance.settings.lock()
+            && let Err(error) = midi.connect(settings.midi_port.as_deref()) {
+                eprintln!("{error}");
+            }
+        }
 
-                                    continue;
-                                }
+        let output_port = instance.settings.lock().ok().and_then(|settings| settings.midi_output_port.clone());
+        if let Ok(mut midi_output) = instance.midi_output.lock()
+        && let Err(error) = midi_output.connect(output_port.as_deref()) {
+            eprintln!("{error}");
+        }
 
-                                // Se non c'e' nessun audio in riproduzione
-                                // e il suono attuale e' premuto
-                                // e il suono attuale non e' in modalita' modifica
-                                audio.play(sound);
-                                thread::sleep(Duration::from_millis(1000));
-                                break;
-                            }
-                        }
-                    }
+        if let Ok(mut settings) = instance.settings.lock() {
+            settings.shortcut_backend_error = settings.shortcut_backend.check().err();
+            settings.keyboard_layout.detect();
+        }
 
-                    thread::sleep(Duration::from_millis(50));
-                }
-            });
+        instance.gamepad.set_repaint_context(cc.egui_ctx.clone());
+        instance.gamepad.start();
+
+        instance.bindings.set_repaint_context(cc.egui_ctx.clone());
+        instance.bindings.set_active_board(instance.boards.active());
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(audio) = instance.audio.lock() && let Ok(mut midi) = instance.midi.lock() {
+            let inputs = TriggerInputs {
+                midi: midi.take_events(),
+                gamepad: instance.gamepad.take_events(),
+            };
+            spawn_trigger_loop(instance.bindings.clone(), audio.remote(), inputs, Arc::clone(&instance.midi_output));
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            instance.web_triggers = Some(WebTriggers::new(cc.egui_ctx.clone()));
         }
 
         instance
@@ -154,15 +246,56 @@ pub fn handle_file(row : usize, name: String, data: Uint8Array) {
     });
 }
 
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// Board archive picked by the user, waiting to be imported.
+    pub static LAST_BOARD: RefCell<Option<Uint8Array>> = RefCell::new(None);
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn handle_board(data: Uint8Array) {
+    LAST_BOARD.with(|slot| {
+        *slot.borrow_mut() = Some(data);
+    });
+}
+
 impl eframe::App for Noisette {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         eframe::set_value(storage, eframe::APP_KEY, self);
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Chiude la registrazione in corso prima che il processo termini
+        if let Ok(mut audio) = self.audio.lock() {
+            audio.shutdown();
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
         let mut sounds = self.sounds.lock().unwrap();
         let mut audio = self.audio.lock().unwrap();
 
+        // Le hotkey cambiano board nel thread dei trigger: la UI lo segue
+        let followed = self.bindings.active_board();
+        let mut board_changed = followed != self.boards.active();
+        if board_changed {
+            self.boards.switch(&mut sounds, followed);
+        }
+
+        #[cfg(target_arch = "wasm32")]
+        if let Some(data) = LAST_BOARD.with(|slot| slot.borrow_mut().take()) {
+            // Nel browser non c'e' una libreria: i file restano nei suoni
+            match archive::import(&data.to_vec(), None) {
+                Ok(board) => {
+                    self.boards.insert(&mut sounds, board);
+                    board_changed = true;
+                }
+                Err(error) => self.last_error = Some(error),
+            }
+        }
+
         #[cfg(target_arch = "wasm32")]
         {
             LAST_SOUND.with(|slot| {
@@ -170,17 +303,73 @@ impl eframe::App for Noisette {
                     if let Some(sound) = sounds.get_mut(row) {
                         sound.path = Some(name);
                         sound.data = Some(data.to_vec());
+                        sound.generator = None;
                     }
                 }
             });
         }
 
+        let side_agnostic_modifiers = self.settings
+            .lock()
+            .is_ok_and(|settings| settings.side_agnostic_modifiers);
+        let shortcut_backend = self.settings
+            .lock()
+            .map(|settings| settings.shortcut_backend)
+            .unwrap_or_default();
+        let toggle_hotkeys_shortcut = self.settings
+            .lock()
+            .ok()
+            .and_then(|settings| settings.action_shortcuts.get(AppAction::ToggleHotkeys).cloned());
+
+        // Impara le etichette del layout dai tasti premuti nella finestra
+        let layout = match self.settings.lock() {
+            Ok(mut settings) => {
+                ctx.input(|input| settings.keyboard_layout.learn(input));
+                settings.keyboard_layout.clone()
+            }
+            Err(_) => KeyboardLayout::default(),
+        };
+
+        let conflict_hints = conflict_hints(&sounds, &shortcut_conflicts(&sounds, &hotkey_shortcuts(&self.settings)));
+        let mut recorded = None;
+
+        for event in audio.poll_events() {
+            if let AudioEvent::Error(message) = event {
+                self.last_error = Some(message);
+            }
+        }
+
         if let Ok(mut settings) = self.settings.lock() && let Some(new_device) = &mut settings.new_device {
             println!("Device changed to: {new_device}");
             audio.set_device(Some(new_device.clone()));
             settings.new_device = None;
         }
 
+        if let Ok(mut settings) = self.settings.lock()
+        && let Some(port) = settings.new_midi_port.take()
+        && let Ok(mut midi) = self.midi.lock()
+        && let Err(error) = midi.connect(port.as_deref()) {
+            self.last_error = Some(error);
+        }
+
+        if let Ok(mut settings) = self.settings.lock()
+        && let Some(port) = settings.new_midi_output_port.take()
+        && let Ok(mut midi_output) = self.midi_output.lock()
+        && let Err(error) = midi_output.connect(port.as_deref()) {
+            self.last_error = Some(error);
+        }
+
+        let midi_monitor = self.midi.lock().map(|midi| midi.monitor()).unwrap_or_default();
+        let gamepad_monitor = self.gamepad.monitor();
+
+        if let Ok(mut settings) = self.settings.lock() && let Some(new_backend) = settings.new_backend.take() {
+            audio.set_backend(new_backend);
+
+            if let Some(device_name) = settings.selected_device_name.clone() {
+                audio.set_device(Some(device_name));
+            }
+        }
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 if ui.button("Add").clicked() {
@@ -193,12 +382,146 @@ impl eframe::App for Noisette {
                     settings.open = !settings.open;
                 }
 
+                let recording_btn = if audio.is_recording() {
+                    egui::Button::new(format!("{STOP_EMOJI} Stop recording"))
+                } else {
+                    egui::Button::new(format!("{RECORD_EMOJI} Record"))
+                };
+
+                if ui.add(recording_btn).clicked() && let Ok(settings) = self.settings.lock() {
+                    if audio.is_recording() {
+                        audio.stop_recording();
+                    } else {
+                        audio.start_recording(settings.recording_target());
+                    }
+                }
+
+                ui.separator();
+
+                let master = audio.state().master;
+                let mute_icon = if master.muted { MUTE_EMOJI } else { VOLUME_EMOJI };
+                if ui.button(mute_icon).on_hover_text("Mute").clicked() {
+                    audio.toggle_mute();
+                }
+
+                let mut volume = master.volume;
+                if ui.add(egui::Slider::new(&mut volume, 0.0..=1.0).show_value(false)).changed() {
+                    audio.set_volume(volume);
+                }
+
+                let armed = self.bindings.armed();
+                let hotkeys_text = if armed { "Hotkeys on" } else { "Hotkeys off" };
+                let toggle_hint = match toggle_hotkeys_shortcut.as_deref() {
+                    Some(shortcut) => format!("Arm or disarm the hotkeys ({})", layout.shortcut_label(shortcut)),
+                    None => "Arm or disarm the hotkeys".to_string(),
+                };
+                if ui.selectable_label(armed, format!("{HOTKEYS_EMOJI} {hotkeys_text}")).on_hover_text(toggle_hint).clicked() {
+                    self.bindings.set_armed(!armed);
+                }
+
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     egui::widgets::global_theme_preference_buttons(ui);
                 });
             });
         });
 
+        egui::TopBottomPanel::top("boards_panel").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                let removable = self.boards.iter().count() > 1;
+                let mut switched = None;
+                let mut removed = None;
+                let mut exported = None;
+
+                for idx in 0..self.boards.iter().count() {
+                    let active = idx == self.boards.active();
+                    let Some(board) = self.boards.get_mut(idx) else {
+                        continue;
+                    };
+
+                    let tab = ui.selectable_label(active, board.name.as_str());
+                    if tab.clicked() && !active {
+                        switched = Some(idx);
+                    }
+
+                    tab.context_menu(|ui| {
+                        ui.text_edit_singleline(&mut board.name);
+
+                        ui.horizontal(|ui| {
+                            ui.label("Interrupt on new:");
+                            egui::ComboBox::from_id_salt(("board_stop_on_new", idx))
+                                .selected_text(stop_on_new_label(board.stop_on_new))
+                                .show_ui(ui, |ui| {
+                                    for value in [None, Some(true), Some(false)] {
+                                        ui.selectable_value(&mut board.stop_on_new, value, stop_on_new_label(value));
+                                    }
+                                });
+                        });
+
+                        if ui.button(format!("{SAVE_EMOJI} Export...")).clicked() {
+                            exported = Some(idx);
+                            ui.close_menu();
+                        }
+
+                        if ui.add_enabled(removable, egui::Button::new(format!("{REMOVE_EMOJI} Remove board"))).clicked() {
+                            removed = Some(idx);
+                            ui.close_menu();
+                        }
+                    });
+                }
+
+                if ui.button("+").on_hover_text("New board").clicked() {
+                    self.boards.add(&mut sounds);
+                    board_changed = true;
+                }
+
+                if ui.button(LOAD_EMOJI).on_hover_text("Import a board").clicked() {
+                    #[cfg(not(target_arch = "wasm32"))]
+                    if let Some(path) = rfd::FileDialog::new().add_filter("Noisette board", &[archive::EXTENSION]).pick_file() {
+                        let library = self.settings.lock().map(|settings| settings.library_dir()).unwrap_or_else(|_| archive::default_library_dir());
+                        let imported = std::fs::read(&path)
+                            .map_err(|error| format!("Cannot read {}: {error}", path.display()))
+                            .and_then(|bytes| archive::import(&bytes, Some(&library)));
+
+                        match imported {
+                            Ok(board) => {
+                                self.boards.insert(&mut sounds, board);
+                                board_changed = true;
+                            }
+                            Err(error) => self.last_error = Some(error),
+                        }
+                    }
+
+                    #[cfg(target_arch = "wasm32")]
+                    trigger_board_picker();
+                }
+
+                if let Some(idx) = exported
+                && let Some(board) = self.boards.get(idx)
+                && let Err(error) = export_board(board, self.boards.sounds_of(idx, &sounds)) {
+                    self.last_error = Some(error);
+                }
+
+                if let Some(idx) = switched {
+                    self.boards.switch(&mut sounds, idx);
+                    board_changed = true;
+                }
+
+                if let Some(idx) = removed {
+                    board_changed |= idx == self.boards.active();
+                    self.boards.remove(&mut sounds, idx);
+                }
+            });
+        });
+
+        if board_changed {
+            // Gli indici delle righe non valgono piu' per la nuova board
+            self.listening_shortcut = None;
+            self.learning_midi = None;
+            self.learning_gamepad = None;
+            self.dragging_index = None;
+            self.pending_conflict = None;
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             let column_count = 6;
 
@@ -231,16 +554,81 @@ impl eframe::App for Noisette {
                                 });
 
                                 // ShortcutRecorder
-                                columns[1].add_sized(
+                                let recorder = columns[1].add_sized(
                                     [0.0, 2.0],
                                     PlatformShortcutRecorder::new(
                                         &mut sound.shortcut,
                                         &mut self.listening_shortcut,
                                         &mut self.last_pressed_keys,
                                         idx
-                                ));
+                                    )
+                                    .sequence(&mut sound.sequence)
+                                    .side_agnostic(side_agnostic_modifiers)
+                                    .backend(shortcut_backend)
+                                    .layout(&layout));
+
+                                if recorder.changed() {
+                                    recorded = Some(ShortcutOwner::Sound(idx));
+                                }
+
+                                if let Some(hint) = conflict_hints.get(&ShortcutOwner::Sound(idx)) {
+                                    let warn_color = columns[1].visuals().warn_fg_color;
+                                    columns[1].colored_label(warn_color, "⚠").on_hover_text(hint);
+                                }
+
+                                egui::ComboBox::from_id_salt(("retrigger", idx))
+                                    .selected_text(sound.retrigger.to_string())
+                                    .width(0.0)
+                                    .show_ui(&mut columns[1], |ui| {
+                                        for policy in RetriggerPolicy::ALL {
+                                            ui.selectable_value(&mut sound.retrigger, policy, policy.to_string());
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text("What the shortcut does while the sound is already playing");
+
+                                egui::ComboBox::from_id_salt(("trigger_mode", idx))
+                                    .selected_text(sound.trigger_mode.to_string())
+                                    .width(0.0)
+                                    .show_ui(&mut columns[1], |ui| {
+                                        for mode in TriggerMode::ALL {
+                                            ui.selectable_value(&mut sound.trigger_mode, mode, mode.to_string());
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text("How the shortcut is pressed. Tap, double tap and long press need a single-chord shortcut.");
+
+                                columns[1].horizontal(|ui| {
+                                    ui.add(InputLearn::new(
+                                        &mut sound.midi,
+                                        &mut self.learning_midi,
+                                        &midi_monitor,
+                                        idx
+                                    )
+                                    .empty_text("No MIDI")
+                                    .prompt("Hit a pad..."))
+                                    .on_hover_text("Learn a MIDI note or CC");
 
-                                show_file_label_with_click(&mut columns[2], sound);
+                                    if sound.midi.is_some() {
+                                        ui.checkbox(&mut sound.velocity_to_volume, "Velocity")
+                                            .on_hover_text("Scale the volume by how hard the pad is hit");
+                                    }
+                                });
+
+                                columns[1].add(InputLearn::new(
+                                    &mut sound.gamepad,
+                                    &mut self.learning_gamepad,
+                                    &gamepad_monitor,
+                                    idx
+                                )
+                                .empty_text("No gamepad")
+                                .prompt("Press a button..."))
+                                .on_hover_text("Learn a gamepad button or stick direction");
+
+                                columns[2].add(GeneratorEditor::new(&mut sound.generator, idx));
+                                if sound.generator.is_none() {
+                                    show_file_label_with_click(&mut columns[2], sound);
+                                }
                                 
                                 /*
                                 let select_btn = if let Some(icon) = self.icons.get("load") {
@@ -256,8 +644,9 @@ impl eframe::App for Noisette {
                                 if columns[3].add_sized([0.0, 2.0], select_btn.min_size(egui::Vec2::ZERO)).clicked() {
                                     #[cfg(not(target_arch = "wasm32"))]
                                     {
-                                        if let Some(path) = rfd::FileDialog::new().add_filter("Audio", &["mp3", "wav"]).pick_file() {
+                                        if let Some(path) = rfd::FileDialog::new().add_filter("Audio", SUPPORTED_EXTENSIONS).pick_file() {
                                             sound.path = Some(path.display().to_string());
+                                            sound.generator = None;
                                         }
                                     }
                                     #[cfg(target_arch = "wasm32")]
@@ -307,20 +696,44 @@ impl eframe::App for Noisette {
                                     egui::Label::new(label).wrap_mode(egui::TextWrapMode::Truncate)
                                 );
 
-                                // Shortcut
-                                columns[1].add_sized(
+                                // Shortcut, evidenziata se in conflitto con un'altra
+                                let shortcut_label = sound.steps()
+                                    .map_or("No Shortcut".to_string(), |steps| layout.sequence_label(&steps));
+                                let hint = conflict_hints.get(&ShortcutOwner::Sound(idx));
+                                let shortcut_text = match hint {
+                                    Some(_) => egui::RichText::new(format!("⚠ {shortcut_label}"))
+                                        .color(columns[1].visuals().warn_fg_color),
+                                    None => egui::RichText::new(shortcut_label),
+                                };
+
+                                let response = columns[1].add_sized(
                                     [0.0, 2.0],
-                                    egui::Label::new(&sound.shortcut
-                                        .clone()
-                                        .map_or("No Shortcut".to_string(), |s| shortcut_as_string(&s)))
-                                        .wrap_mode(egui::TextWrapMode::Truncate)
+                                    egui::Label::new(shortcut_text).wrap_mode(egui::TextWrapMode::Truncate)
                                 );
+                                if let Some(hint) = hint {
+                                    response.on_hover_text(hint);
+                                }
+
+                                if let Some(midi) = &sound.midi {
+                                    columns[1].small(midi.to_string());
+                                }
+
+                                if let Some(gamepad) = &sound.gamepad {
+                                    columns[1].small(gamepad.to_string());
+                                }
 
                                 // File name
-                                show_file_label_with_click(&mut columns[2], sound);
+                                if let Some(generator) = &sound.generator {
+                                    columns[2].add_sized(
+                                        [0.0, 2.0],
+                                        egui::Label::new(generator.to_string()).wrap_mode(egui::TextWrapMode::Truncate)
+                                    );
+                                } else {
+                                    show_file_label_with_click(&mut columns[2], sound);
+                                }
 
                                 // Play / Stop button
-                                if audio.is_playing(Some(sound.clone())) {
+                                if audio.is_playing(Some(sound)) {
                                     /*
                                     let stop_btn = if let Some(icon) = self.icons.get("stop") {
                                         egui::Button::image(icon)
@@ -348,7 +761,9 @@ impl eframe::App for Noisette {
 
                                     if columns[3].add_sized([0.0, 2.0], play_btn).clicked() {
                                         audio.stop_all();
-                                        audio.play(sound);
+                                        if let Err(error) = audio.play(sound) {
+                                            self.last_error = Some(error);
+                                        }
                                     }
                                 }
 
@@ -390,19 +805,511 @@ impl eframe::App for Noisette {
 
             if let Some(idx) = to_remove {
                 sounds.remove(idx);
+                // Gli indici sono cambiati
+                self.pending_conflict = None;
             }
 
             if let Ok(mut settings) = self.settings.lock() && settings.open {
                 settings.show(ctx, frame);
             }
 
+            if let Ok(mut settings) = self.settings.lock()
+            && let Some(owner) = settings.changed_shortcut.take() {
+                recorded = Some(owner);
+            }
+
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
                 egui::warn_if_debug_build(ui);
 
+                if let Some(error) = &self.last_error {
+                    let mut dismissed = false;
+                    ui.horizontal(|ui| {
+                        ui.colored_label(ui.visuals().error_fg_color, format!("⚠ {error}"));
+                        dismissed = ui.small_button("❌").clicked();
+                    });
+                    if dismissed {
+                        self.last_error = None;
+                    }
+                }
+
                 if cfg!(debug_assertions) && ui.button("Clear Data").clicked() {
                     sounds.clear();
                 }
             });
         });
+
+        if let Some(owner) = recorded {
+            self.pending_conflict = shortcut_conflicts(&sounds, &hotkey_shortcuts(&self.settings))
+                .iter()
+                .find(|conflict| conflict.involves(owner))
+                .map(|conflict| (owner, conflict.other(owner)));
+        }
+
+        if let Some((recorded, older)) = self.pending_conflict {
+            let mut resolution = None;
+
+            egui::Window::new("Shortcut conflict")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "The shortcut of {} conflicts with {}.",
+                        owner_label(recorded, &sounds),
+                        owner_label(older, &sounds),
+                    ));
+
+                    ui.horizontal(|ui| {
+                        if ui.button("Reassign").on_hover_text("Record a new shortcut for the older binding").clicked() {
+                            resolution = Some(ConflictResolution::Reassign);
+                        }
+                        if ui.button("Clear").on_hover_text("Remove the shortcut of the older binding").clicked() {
+                            resolution = Some(ConflictResolution::Clear);
+                        }
+                        if ui.button("Keep both").clicked() {
+                            resolution = Some(ConflictResolution::KeepBoth);
+                        }
+                    });
+                });
+
+            if let Some(resolution) = resolution {
+                self.pending_conflict = None;
+
+                match (resolution, older) {
+                    (ConflictResolution::KeepBoth, _) => {}
+                    (ConflictResolution::Clear, ShortcutOwner::Sound(idx)) => {
+                        if let Some(sound) = sounds.get_mut(idx) {
+                            sound.shortcut = None;
+                            sound.sequence.clear();
+                        }
+                    }
+                    (ConflictResolution::Clear, ShortcutOwner::ToggleRecording) => {
+                        if let Ok(mut settings) = self.settings.lock() {
+                            settings.record_shortcut = None;
+                        }
+                    }
+                    (ConflictResolution::Reassign, ShortcutOwner::Sound(idx)) => {
+                        if let Some(sound) = sounds.get_mut(idx) {
+                            sound.editing = true;
+                            self.listening_shortcut = Some(idx);
+                            self.last_pressed_keys = None;
+                        }
+                    }
+                    (ConflictResolution::Reassign, ShortcutOwner::ToggleRecording) => {
+                        if let Ok(mut settings) = self.settings.lock() {
+                            settings.reassign_record_shortcut();
+                        }
+                    }
+                    (ConflictResolution::Clear, ShortcutOwner::Action(action)) => {
+                        if let Ok(mut settings) = self.settings.lock() {
+                            *settings.action_shortcuts.get_mut(action) = None;
+                        }
+                    }
+                    (ConflictResolution::Reassign, ShortcutOwner::Action(action)) => {
+                        if let Ok(mut settings) = self.settings.lock() {
+                            settings.reassign_action_shortcut(action);
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Ok(mut settings) = self.settings.lock() {
+            settings.master_volume = audio.state().master.volume;
+            // Senza informazioni sul focus la finestra si considera attiva
+            let focused = ctx.input(|input| input.viewport().focused.unwrap_or(true));
+            let base = base_snapshot(&settings, focused);
+            let hotkeys = hotkey_bindings(&settings);
+            if !self.published.is_current(&self.boards, &sounds, &hotkeys, &base) {
+                self.published.boards = self.boards.iter()
+                    .enumerate()
+                    .map(|(idx, board)| Board {
+                        name: board.name.clone(),
+                        sounds: self.boards.sounds_of(idx, &sounds).iter().map(Sound::without_data).collect(),
+                        stop_on_new: board.stop_on_new,
+                    })
+                    .collect();
+                self.bindings.publish(trigger_snapshot(&self.published.boards, &hotkeys, base.clone()));
+                self.published.hotkeys = hotkeys;
+                self.published.base = base;
+            }
+
+            // Un cambio fatto nella UI vale anche per le hotkey
+            if self.boards.active() != followed {
+                self.bindings.set_active_board(self.boards.active());
+            }
+
+            #[cfg(target_arch = "wasm32")]
+            if let Some(triggers) = &mut self.web_triggers {
+                triggers.poll(&self.bindings, &mut audio, ctx);
+            }
+            #[cfg(target_arch = "wasm32")]
+            if let Ok(mut midi_output) = self.midi_output.lock() {
+                midi_output.update(&pad_values(&sounds, &audio.state(), &settings.feedback_profile, &mut self.pad_files));
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ConflictResolution {
+    Reassign,
+    Clear,
+    KeepBoth,
+}
+
+type OwnedShortcut = (ShortcutOwner, Vec<Vec<SerializableKeycode>>);
+
+/// The record hotkey and the app action hotkeys, valid on every board.
+fn hotkey_shortcuts(settings: &Mutex<SettingsWindow>) -> Vec<OwnedShortcut> {
+    let mut shortcuts = Vec::new();
+
+    if let Ok(settings) = settings.lock() {
+        if let Some(shortcut) = &settings.record_shortcut {
+            shortcuts.push((ShortcutOwner::ToggleRecording, vec![shortcut.clone()]));
+        }
+
+        for (action, shortcut) in settings.action_shortcuts.iter() {
+            shortcuts.push((ShortcutOwner::Action(action), vec![shortcut.clone()]));
+        }
+    }
+
+    shortcuts
+}
+
+/// Conflicts among the sound shortcuts of the active board and the hotkeys.
+fn shortcut_conflicts(sounds: &[Sound], hotkeys: &[OwnedShortcut]) -> Vec<Conflict> {
+    let mut shortcuts = hotkeys.to_vec();
+
+    for (idx, sound) in sounds.iter().enumerate() {
+        if let Some(steps) = sound.steps() {
+            shortcuts.push((ShortcutOwner::Sound(idx), steps));
+        }
+    }
+
+    // Tap, doppio tap e pressione lunga dello stesso tasto convivono
+    let gesture = |owner: ShortcutOwner| match owner {
+        ShortcutOwner::Sound(idx) => sounds
+            .get(idx)
+            .filter(|sound| sound.steps().is_some_and(|steps| steps.len() == 1))
+            .map(|sound| sound.trigger_mode)
+            .filter(|mode| mode.is_gesture()),
+        _ => None,
+    };
+
+    find_conflicts(&shortcuts)
+        .into_iter()
+        .filter(|conflict| match (gesture(conflict.first), gesture(conflict.second)) {
+            (Some(first), Some(second)) => first == second,
+            _ => true,
+        })
+        .collect()
+}
+
+/// Hover text for every owner involved in at least one conflict.
+fn conflict_hints(sounds: &[Sound], conflicts: &[Conflict]) -> HashMap<ShortcutOwner, String> {
+    let mut hints: HashMap<ShortcutOwner, String> = HashMap::new();
+
+    for conflict in conflicts {
+        for owner in [conflict.first, conflict.second] {
+            let other = owner_label(conflict.other(owner), sounds);
+            let line = match conflict.kind {
+                ConflictKind::Duplicate => format!("Same shortcut as {other}"),
+                ConflictKind::Overlap => format!("Overlaps with {other}"),
+            };
+
+            let hint = hints.entry(owner).or_default();
+            if !hint.is_empty() {
+                hint.push('\n');
+            }
+            hint.push_str(&line);
+        }
+    }
+
+    hints
+}
+
+/// Saves `board` as an archive, through a save dialog or a download.
+fn export_board(board: &Board, sounds: &[Sound]) -> Result<(), String> {
+    let bytes = archive::export(board, sounds)?;
+    let file_name = format!("{}.{}", board.name, archive::EXTENSION);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(path) = rfd::FileDialog::new()
+        .add_filter("Noisette board", &[archive::EXTENSION])
+        .set_file_name(file_name)
+        .save_file() {
+        std::fs::write(&path, bytes).map_err(|error| format!("Cannot write {}: {error}", path.display()))?;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    download_file(&file_name, &bytes);
+
+    Ok(())
+}
+
+/// The "Interrupt on new" override of a board.
+fn stop_on_new_label(stop_on_new: Option<bool>) -> &'static str {
+    match stop_on_new {
+        None => "As in settings",
+        Some(true) => "On",
+        Some(false) => "Off",
+    }
+}
+
+fn owner_label(owner: ShortcutOwner, sounds: &[Sound]) -> String {
+    match owner {
+        ShortcutOwner::Sound(idx) => {
+            let name = sounds
+                .get(idx)
+                .and_then(|sound| sound.name.as_deref())
+                .filter(|name| !name.is_empty())
+                .unwrap_or("No Name");
+            format!("\"{name}\"")
+        }
+        ShortcutOwner::ToggleRecording => "the record hotkey".to_string(),
+        ShortcutOwner::Action(action) => format!("the \"{action}\" hotkey"),
+    }
+}
+
+/// What the published [`TriggerSnapshot`] was built from, so a new one is
+/// only built when the sounds or the settings change.
+#[derive(Default)]
+struct PublishedSnapshot {
+    /// Every board, its sounds without their file contents.
+    boards: Vec<Board>,
+    hotkeys: Vec<Binding>,
+    base: TriggerSnapshot,
+}
+
+impl PublishedSnapshot {
+    fn is_current(&self, boards: &Boards, sounds: &[Sound], hotkeys: &[Binding], base: &TriggerSnapshot) -> bool {
+        self.base == *base
+            && self.hotkeys == hotkeys
+            && self.boards.len() == boards.iter().count()
+            && self.boards.iter().zip(boards.iter()).enumerate().all(|(idx, (published, board))| {
+                let board_sounds = boards.sounds_of(idx, sounds);
+                published.stop_on_new == board.stop_on_new
+                    && published.sounds.len() == board_sounds.len()
+                    && published.sounds.iter().zip(board_sounds).all(|(published, sound)| published.eq_without_data(sound))
+            })
+    }
+}
+
+/// The snapshot without any binding: just the settings.
+fn base_snapshot(settings: &SettingsWindow, focused: bool) -> TriggerSnapshot {
+    TriggerSnapshot {
+        stop_on_new: settings.stop_on_new,
+        recording: settings.recording_target(),
+        sequence_timeout: settings.sequence_timeout(),
+        gesture_timing: settings.gesture_timing(),
+        shortcut_backend: settings.shortcut_backend,
+        keyboard_paused: settings.focus_only && !focused,
+        feedback_profile: settings.feedback_profile.clone(),
+        ..TriggerSnapshot::default()
+    }
+}
+
+/// The hotkeys of the recording and of the app actions, the same on every board.
+fn hotkey_bindings(settings: &SettingsWindow) -> Vec<Binding> {
+    let mut bindings = Vec::new();
+
+    if let Some(shortcut) = &settings.record_shortcut {
+        bindings.push(Binding {
+            steps: vec![shortcut.clone()],
+            action: TriggerAction::ToggleRecording,
+            mode: TriggerMode::Press,
+        });
+    }
+
+    for (action, shortcut) in settings.action_shortcuts.iter() {
+        bindings.push(Binding {
+            steps: vec![shortcut.clone()],
+            action: TriggerAction::App(action),
+            mode: TriggerMode::Press,
+        });
+    }
+
+    bindings
+}
+
+/// Adds the bindings of every board, whose sounds are already without their
+/// file contents, to `base`.
+fn trigger_snapshot(boards: &[Board], hotkeys: &[Binding], mut base: TriggerSnapshot) -> TriggerSnapshot {
+    base.boards = boards.iter()
+        .map(|board| board_bindings(board, hotkeys))
+        .collect();
+    base
+}
+
+fn board_bindings(board: &Board, hotkeys: &[Binding]) -> BoardBindings {
+    let mut bindings = BoardBindings {
+        bindings: hotkeys.to_vec(),
+        // Un suono in modifica non si attiva, ma il suo pad resta acceso
+        pads: board.sounds.iter()
+            .filter(|sound| sound.midi.is_some())
+            .map(|sound| Arc::new(sound.clone()))
+            .collect(),
+        stop_on_new: board.stop_on_new,
+        ..BoardBindings::default()
+    };
+
+    // I suoni in modalita' modifica non vengono attivati
+    for sound in board.sounds.iter().filter(|sound| !sound.editing) {
+        let sound = Arc::new(sound.clone());
+
+        if let Some(steps) = sound.steps() {
+            // I gesti valgono per un accordo solo: una sequenza scatta alla pressione
+            let mode = match sound.trigger_mode {
+                mode if mode.is_gesture() && steps.len() > 1 => TriggerMode::Press,
+                mode => mode,
+            };
+            let binding = Binding {
+                steps,
+                action: TriggerAction::PlaySound(Arc::clone(&sound)),
+                mode,
+            };
+
+            if mode.is_gesture() {
+                bindings.gesture_bindings.push(binding);
+            } else {
+                bindings.bindings.push(binding);
+            }
+        }
+
+        if let Some(trigger) = sound.midi {
+            bindings.midi_bindings.push(MidiBinding {
+                trigger,
+                velocity_to_volume: sound.velocity_to_volume,
+                action: TriggerAction::PlaySound(Arc::clone(&sound)),
+            });
+        }
+
+        if let Some(trigger) = sound.gamepad {
+            bindings.gamepad_bindings.push(GamepadBinding {
+                trigger,
+                action: TriggerAction::PlaySound(sound),
+            });
+        }
+    }
+
+    bindings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::MidiTrigger;
+    use SerializableKeycode::*;
+
+    fn sound_on(chords: &[&[SerializableKeycode]], trigger_mode: TriggerMode) -> Sound {
+        Sound {
+            shortcut: Some(chords[0].to_vec()),
+            sequence: chords[1..].iter().map(|chord| chord.to_vec()).collect(),
+            trigger_mode,
+            editing: false,
+            ..Sound::default()
+        }
+    }
+
+    #[test]
+    fn pads_stay_lit_while_their_sound_is_edited() {
+        let trigger = MidiTrigger::Note { channel: 0, note: 36 };
+        let board = Board {
+            name: "Pads".to_string(),
+            sounds: vec![
+                Sound { midi: Some(trigger), editing: true, ..Sound::default() },
+                Sound { editing: false, ..Sound::default() },
+            ],
+            stop_on_new: None,
+        };
+
+        let bindings = board_bindings(&board, &[]);
+        assert_eq!(bindings.midi_bindings, []);
+        assert_eq!(bindings.pads, [Arc::new(board.sounds[0].clone())]);
+    }
+
+    #[test]
+    fn gestures_on_the_same_chord_do_not_conflict() {
+        let sounds = [
+            sound_on(&[&[F13]], TriggerMode::Tap),
+            sound_on(&[&[F13]], TriggerMode::DoubleTap),
+            sound_on(&[&[F13]], TriggerMode::LongPress),
+        ];
+
+        assert_eq!(shortcut_conflicts(&sounds, &[]), []);
+    }
+
+    #[test]
+    fn the_same_gesture_twice_conflicts() {
+        let sounds = [
+            sound_on(&[&[F13]], TriggerMode::DoubleTap),
+            sound_on(&[&[F13]], TriggerMode::Tap),
+            sound_on(&[&[F13]], TriggerMode::DoubleTap),
+        ];
+
+        assert_eq!(
+            shortcut_conflicts(&sounds, &[]),
+            [Conflict {
+                first: ShortcutOwner::Sound(0),
+                second: ShortcutOwner::Sound(2),
+                kind: ConflictKind::Duplicate,
+            }]
+        );
+    }
+
+    #[test]
+    fn a_gesture_conflicts_with_a_plain_press() {
+        let sounds = [
+            sound_on(&[&[F13]], TriggerMode::Press),
+            sound_on(&[&[F13]], TriggerMode::LongPress),
+        ];
+        let hotkeys = [(ShortcutOwner::Action(AppAction::StopAll), vec![vec![F13]])];
+
+        let conflicts = shortcut_conflicts(&sounds, &hotkeys);
+        let pairs: Vec<(ShortcutOwner, ShortcutOwner)> = conflicts.iter().map(|conflict| (conflict.first, conflict.second)).collect();
+        assert_eq!(
+            pairs,
+            [
+                (ShortcutOwner::Action(AppAction::StopAll), ShortcutOwner::Sound(0)),
+                (ShortcutOwner::Action(AppAction::StopAll), ShortcutOwner::Sound(1)),
+                (ShortcutOwner::Sound(0), ShortcutOwner::Sound(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_gesture_sequence_is_not_a_gesture() {
+        // Solo il primo passo di una sequenza e' un gesto: le due si sovrappongono
+        let sounds = [
+            sound_on(&[&[F13]], TriggerMode::Tap),
+            sound_on(&[&[F13], &[Key1]], TriggerMode::Tap),
+        ];
+
+        assert_eq!(shortcut_conflicts(&sounds, &[]).len(), 1);
+    }
+
+    #[test]
+    fn only_the_hotkeys_conflict_across_boards() {
+        let first = Board {
+            sounds: vec![sound_on(&[&[Ctrl, Key1]], TriggerMode::Press)],
+            ..Board::new("First")
+        };
+        let second = Board {
+            sounds: vec![sound_on(&[&[Ctrl, Key1]], TriggerMode::Press), sound_on(&[&[F5]], TriggerMode::Press)],
+            ..Board::new("Second")
+        };
+        let hotkeys = [(ShortcutOwner::Action(AppAction::NextBoard), vec![vec![F5]])];
+
+        // Ogni board viene controllata da sola, le hotkey valgono su tutte
+        assert_eq!(shortcut_conflicts(&first.sounds, &hotkeys), []);
+        assert_eq!(
+            shortcut_conflicts(&second.sounds, &hotkeys),
+            [Conflict {
+                first: ShortcutOwner::Action(AppAction::NextBoard),
+                second: ShortcutOwner::Sound(1),
+                kind: ConflictKind::Duplicate,
+            }]
+        );
     }
 }
@@ -1,61 +1,108 @@
 use rodio::cpal::{self, Device};
 use rodio::cpal::traits::{HostTrait, DeviceTrait};
+use rodio::dynamic_mixer::{self, DynamicMixerController};
+use rodio::buffer::SamplesBuffer;
+use rodio::source::Zero;
 use rodio::OutputStreamHandle;
-use rodio::{Decoder, OutputStream, Sink};
+use rodio::{Decoder, OutputStream, Sink, Source};
 use std::fs::exists;
 use std::path::Path;
 use std::time::{Duration, Instant};
-use std::{fs::File, io::BufReader, sync::{Arc, Mutex}};
-use std::sync::mpsc::{self, Sender, Receiver};
-use std::collections::HashMap;
-use std::thread;
+use std::{fs::File, io::BufReader, sync::{Arc, Mutex, RwLock}};
+use std::sync::mpsc::{self, Sender, Receiver, RecvTimeoutError};
+use std::collections::{HashMap, HashSet};
+use std::thread::{self, JoinHandle};
 
 use crate::Sound;
 
-use super::interface::AudioBackend;
+use super::interface::{AudioBackend, AudioEvent, MasterVolume, PlaybackState};
+use super::master::{MasterGain, SharedGain};
+use super::null::NullAudio;
+use super::opus;
+use super::{AudioBackendKind, RecordingTarget};
+use super::recorder::{RecordingTap, SessionRecorder};
+use super::synth::SYNTH_SAMPLE_RATE;
+
+const MIX_CHANNELS: u16 = 2;
+const MIX_SAMPLE_RATE: u32 = 44_100;
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
 
 // Api
 
 pub struct DesktopAudio {
-    sinks: Arc<Mutex<HashMap<String, Arc<Sink>>>>,
+    /// Every playing instance, grouped by [`Sound::key`].
+    sinks: Arc<Mutex<HashMap<String, Vec<Arc<Sink>>>>>,
+    // Prima dello stream: va chiuso mentre il mix scorre ancora
+    recorder: SessionRecorder,
     _stream: OutputStream,
     stream_handle: OutputStreamHandle,
+    mixer: Arc<DynamicMixerController<f32>>,
+    master_gain: SharedGain,
 }
 
 impl DesktopAudio {
-    pub fn new() -> Self {
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        Self {
+    /// Opens the default output device, `None` if the machine has none.
+    pub fn try_new() -> Option<Self> {
+        let (_stream, stream_handle) = match OutputStream::try_default() {
+            Ok(stream) => stream,
+            Err(error) => {
+                eprintln!("Cannot open the default audio output: {error}");
+                return None;
+            }
+        };
+        let recorder = SessionRecorder::new(MIX_CHANNELS, MIX_SAMPLE_RATE);
+        let master_gain = SharedGain::new(1.0);
+        let mixer = create_mixer(&stream_handle, &recorder, &master_gain);
+        Some(Self {
             sinks: Arc::new(Mutex::new(HashMap::new())),
+            recorder,
             _stream,
             stream_handle,
+            mixer,
+            master_gain,
+        })
+    }
+
+    pub fn start_recording(&mut self, target: RecordingTarget) -> Result<(), String> {
+        let path = self.recorder.start(&target)?;
+        println!("Recording session to {}", path.display());
+        Ok(())
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some(path) = self.recorder.stop() {
+            println!("Recording saved to {}", path.display());
         }
     }
 
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_recording()
+    }
+
     pub fn clean_finished_sinks(&self) {
         let mut sinks = self.sinks.lock().unwrap();
-        sinks.retain(|_, s| !s.empty());
+        sinks.retain(|_, instances| {
+            instances.retain(|s| !s.empty());
+            !instances.is_empty()
+        });
     }
 
-    pub fn stop_all(&mut self) {
-        let mut sinks = self.sinks.lock().unwrap();
-        for (_, sink) in sinks.drain() {
-            sink.stop();
-        }
+    pub fn playing_keys(&self) -> HashSet<String> {
+        let sinks = self.sinks.lock().unwrap();
+        sinks.iter()
+            .filter(|(_, instances)| instances.iter().any(|sink| !sink.empty()))
+            .map(|(key, _)| key.clone())
+            .collect()
     }
 
     pub fn set_device(&mut self, device_name: Option<String>) {
         if let Some(device) = get_device_from_name(device_name) {
             if let Ok((_new_stream, new_handle)) = OutputStream::try_from_device(&device) {
                 // Ferma tutti i sink esistenti
-                if let Ok(mut sinks) = self.sinks.lock() {
-                    for (_, sink) in sinks.iter_mut() {
-                        sink.stop();
-                    }
-                    sinks.clear(); // rimuovi tutti i sink
-                }
+                self.stop_all();
 
                 // Aggiorna lo stream e il device
+                self.mixer = create_mixer(&new_handle, &self.recorder, &self.master_gain);
                 self._stream = _new_stream;
                 self.stream_handle = new_handle;
             } else {
@@ -68,63 +115,107 @@ impl DesktopAudio {
 }
 
 impl AudioBackend for DesktopAudio {
-    fn play(&mut self, sound: &Sound) {
+    fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String> {
         self.clean_finished_sinks();
 
-        let path_string = match &sound.path {
-            Some(p) => p.clone(),
-            None => {
-                eprintln!("Sound path is None!");
-                return;
-            }
-        };
+        let key = sound.key().ok_or("Sound path is None!")?;
 
-        let path = Path::new(&path_string);
-        if !exists(path).expect("An error occurred") { return; }
+        let source: Box<dyn Source<Item = f32> + Send> = match &sound.generator {
+            Some(generator) => Box::new(SamplesBuffer::new(1, SYNTH_SAMPLE_RATE, generator.render())),
+            None => decode_file(&key)?,
+        };
 
-        let file = File::open(&path).unwrap();
-        let source = Decoder::new(BufReader::new(file)).unwrap();
+        // Ogni sink passa dal mixer comune, cosi' la registrazione cattura tutto
+        let (sink, queue) = Sink::new_idle();
+        self.mixer.add(queue);
 
-        let sink = Arc::new(Sink::try_new(&self.stream_handle).unwrap());
+        let sink = Arc::new(sink);
+        sink.set_volume(gain);
         sink.as_ref().append(source);
 
+        // Un'altra istanza dello stesso suono si somma a quelle gia' in riproduzione
         let mut sinks = self.sinks.lock().unwrap();
-        sinks.insert(path_string, sink);
+        sinks.entry(key).or_default().push(sink);
+        Ok(())
     }
 
     fn stop(&mut self, sound: &Sound) {
         let mut sinks = self.sinks.lock().unwrap();
-        if let Some(path) = &sound.path && let Some(sink) = sinks.remove(path) {
-            sink.stop();
+        if let Some(key) = sound.key() && let Some(instances) = sinks.remove(&key) {
+            for sink in instances {
+                sink.stop();
+            }
         }
     }
 
     fn stop_all(&mut self) {
         let mut sinks = self.sinks.lock().unwrap();
-        for (_, sink) in sinks.iter() {
+        for sink in sinks.values().flatten() {
             sink.stop();
         }
         sinks.clear(); // Rimuove tutti i riferimenti dopo lo stop
     }
 
-    fn is_playing(&self, sound: Option<Sound>) -> bool {
+    fn is_playing(&self, sound: Option<&Sound>) -> bool {
         self.clean_finished_sinks();
         let sinks = self.sinks.lock().unwrap();
 
         match sound {
             Some(sound) => {
-                if let Some(path) = &sound.path {
-                    sinks.get(path).map_or(false, |sink| !sink.empty())
+                if let Some(key) = sound.key() {
+                    sinks.get(&key).is_some_and(|instances| instances.iter().any(|sink| !sink.empty()))
                 } else {
                     false
                 }
             }
-            None => sinks.values().any(|sink| !sink.empty()),
+            None => sinks.values().flatten().any(|sink| !sink.empty()),
         }
     }
 
 }
 
+pub(super) fn decode_file(path_string: &str) -> Result<Box<dyn Source<Item = f32> + Send>, String> {
+    let path = Path::new(path_string);
+    if !exists(path).unwrap_or(false) {
+        return Err(format!("Sound file not found: {path_string}"));
+    }
+
+    // Symphonia non decodifica Opus: ci pensa il decoder dedicato
+    if opus::is_ogg_opus(path) {
+        return opus::decode(path)
+            .map(|source| Box::new(source) as Box<dyn Source<Item = f32> + Send>)
+            .map_err(|error| format!("Cannot play {path_string}: {error}"));
+    }
+
+    let file = File::open(path)
+        .map_err(|error| format!("Cannot open {path_string}: {error}"))?;
+
+    Decoder::new(BufReader::new(file))
+        .map(|decoder| Box::new(decoder.convert_samples()) as Box<dyn Source<Item = f32> + Send>)
+        .map_err(|error| format!("Cannot play {path_string}: unsupported or corrupted audio file ({error})"))
+}
+
+/// Creates the mixer every sink plays into and routes it, through the
+/// recording tap and the master gain, to the given output stream. The
+/// recording is taken before the master gain, so muting does not blank it.
+fn create_mixer(
+    stream_handle: &OutputStreamHandle,
+    recorder: &SessionRecorder,
+    master_gain: &SharedGain,
+) -> Arc<DynamicMixerController<f32>> {
+    let (controller, mixer) = dynamic_mixer::mixer::<f32>(MIX_CHANNELS, MIX_SAMPLE_RATE);
+
+    // Il mixer termina quando non ha sorgenti: il silenzio lo tiene in vita
+    controller.add(Zero::<f32>::new(MIX_CHANNELS, MIX_SAMPLE_RATE));
+
+    let output = MasterGain::new(RecordingTap::new(mixer, recorder.slot()), master_gain.clone());
+    if let Err(error) = stream_handle.play_raw(output) {
+        eprintln!("Cannot start the output mixer: {error}");
+    }
+
+    controller
+}
+
 pub fn get_output_devices() -> Vec<String> {
     let host = cpal::default_host();
     host.output_devices()
@@ -157,90 +248,562 @@ pub fn get_device_from_name(name: Option<String>) -> Option<Device> {
 // Handler
 
 pub enum AudioCommand {
-    Play(Sound),
+    Play(Sound, f32),
+    /// Played through [`AudioBackend::trigger`], deciding on the current state.
+    Trigger(Sound, f32, bool),
     Stop(Sound),
     StopAll,
-    IsPlaying(Sender<bool>, Option<Sound>),
-    SetDevice(Option<String>)
+    SetDevice(Option<String>),
+    SetBackend(AudioBackendKind),
+    StartRecording(RecordingTarget),
+    StopRecording,
+    SetVolume(f32),
+    NudgeVolume(f32),
+    ToggleMute,
+    /// Finalizes the recording and ends the audio thread.
+    Shutdown,
+}
+
+/// Backend actually driven by the audio thread.
+enum DesktopEngine {
+    Device(DesktopAudio),
+    Null(NullAudio),
+}
+
+impl DesktopEngine {
+    fn new(kind: AudioBackendKind) -> Self {
+        match kind {
+            AudioBackendKind::Device => match DesktopAudio::try_new() {
+                Some(audio) => DesktopEngine::Device(audio),
+                None => {
+                    eprintln!("Falling back to the null audio backend");
+                    DesktopEngine::Null(NullAudio::new())
+                }
+            },
+            AudioBackendKind::Null => DesktopEngine::Null(NullAudio::new()),
+        }
+    }
+
+    fn backend(&mut self) -> &mut dyn AudioBackend {
+        match self {
+            DesktopEngine::Device(audio) => audio,
+            DesktopEngine::Null(audio) => audio,
+        }
+    }
+
+    fn tick(&mut self, elapsed: Duration) {
+        match self {
+            DesktopEngine::Device(audio) => audio.clean_finished_sinks(),
+            DesktopEngine::Null(audio) => {
+                // Nessuno ascolta: scarta il mix e fai avanzare il tempo reale
+                audio.advance(elapsed);
+                audio.take_output();
+            }
+        }
+    }
+
+    fn set_device(&mut self, device: Option<String>) {
+        if let DesktopEngine::Device(audio) = self {
+            audio.set_device(device);
+        }
+    }
+
+    fn set_master_gain(&mut self, gain: f32) {
+        match self {
+            DesktopEngine::Device(audio) => audio.master_gain.set(gain),
+            DesktopEngine::Null(audio) => audio.set_master_gain(gain),
+        }
+    }
+
+    fn start_recording(&mut self, target: RecordingTarget) -> Result<(), String> {
+        match self {
+            DesktopEngine::Device(audio) => audio.start_recording(target),
+            DesktopEngine::Null(_) => Err("Recording is not available with the null audio backend".to_string()),
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        if let DesktopEngine::Device(audio) = self {
+            audio.stop_recording();
+        }
+    }
+
+    fn snapshot(&self, master: MasterVolume) -> PlaybackState {
+        match self {
+            DesktopEngine::Device(audio) => PlaybackState {
+                playing: audio.playing_keys(),
+                recording: audio.is_recording(),
+                master,
+            },
+            DesktopEngine::Null(audio) => PlaybackState {
+                playing: audio.playing_keys(),
+                recording: false,
+                master,
+            },
+        }
+    }
+}
+
+/// Audio thread side of the handler: keeps the shared state up to date and
+/// tells the UI about every change.
+struct Publisher {
+    state: Arc<RwLock<PlaybackState>>,
+    events: Sender<AudioEvent>,
+    repaint: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl Publisher {
+    fn send(&self, event: AudioEvent) {
+        let _ = self.events.send(event);
+    }
+
+    fn request_repaint(&self) {
+        if let Ok(repaint) = self.repaint.lock() && let Some(ctx) = repaint.as_ref() {
+            ctx.request_repaint();
+        }
+    }
+
+    fn error(&self, message: String) {
+        eprintln!("{message}");
+        self.send(AudioEvent::Error(message));
+        self.request_repaint();
+    }
+
+    fn sync(&self, engine: &DesktopEngine, master: MasterVolume) {
+        let snapshot = engine.snapshot(master);
+
+        let Ok(mut state) = self.state.write() else {
+            return;
+        };
+
+        if *state == snapshot {
+            return;
+        }
+
+        for key in snapshot.playing.difference(&state.playing) {
+            self.send(AudioEvent::Started(key.clone()));
+        }
+        for key in state.playing.difference(&snapshot.playing) {
+            self.send(AudioEvent::Finished(key.clone()));
+        }
+
+        *state = snapshot;
+        drop(state);
+
+        self.request_repaint();
+    }
+}
+
+/// Cheap, cloneable handle to the audio thread: sends commands and reads the
+/// published playback state, without going through the handler's mutex.
+#[derive(Clone)]
+pub struct DesktopAudioRemote {
+    sender: Sender<AudioCommand>,
+    state: Arc<RwLock<PlaybackState>>,
+}
+
+impl DesktopAudioRemote {
+    pub fn state(&self) -> PlaybackState {
+        self.state.read().map(|state| state.clone()).unwrap_or_default()
+    }
+
+    pub fn set_backend(&mut self, kind: AudioBackendKind) {
+        let _ = self.sender.send(AudioCommand::SetBackend(kind));
+    }
+
+    pub fn set_device(&mut self, device: Option<String>) {
+        let _ = self.sender.send(AudioCommand::SetDevice(device));
+    }
+
+    pub fn start_recording(&mut self, target: RecordingTarget) {
+        let _ = self.sender.send(AudioCommand::StartRecording(target));
+    }
+
+    pub fn stop_recording(&mut self) {
+        let _ = self.sender.send(AudioCommand::StopRecording);
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.state.read().is_ok_and(|state| state.recording)
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        let _ = self.sender.send(AudioCommand::SetVolume(volume));
+    }
+
+    /// Changes the master volume by `step`, relative to the audio thread's value.
+    pub fn nudge_volume(&mut self, step: f32) {
+        let _ = self.sender.send(AudioCommand::NudgeVolume(step));
+    }
+
+    pub fn toggle_mute(&mut self) {
+        let _ = self.sender.send(AudioCommand::ToggleMute);
+    }
+}
+
+impl AudioBackend for DesktopAudioRemote {
+    fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String> {
+        // Gli errori arrivano in seguito come AudioEvent::Error
+        self.sender
+            .send(AudioCommand::Play(sound.clone(), gain))
+            .map_err(|_| "The audio thread is not running".to_string())
+    }
+
+    fn stop(&mut self, sound: &Sound) {
+        let _ = self.sender.send(AudioCommand::Stop(sound.clone()));
+    }
+
+    fn stop_all(&mut self) {
+        let _ = self.sender.send(AudioCommand::StopAll);
+    }
+
+    fn is_playing(&self, sound: Option<&Sound>) -> bool {
+        self.state.read().is_ok_and(|state| state.is_playing(sound))
+    }
+
+    fn trigger(&mut self, sound: &Sound, gain: f32, stop_on_new: bool) -> Result<(), String> {
+        // Lo stato pubblicato puo' essere indietro di qualche comando: due
+        // pressioni ravvicinate lo vedrebbero entrambe fermo
+        self.sender
+            .send(AudioCommand::Trigger(sound.clone(), gain, stop_on_new))
+            .map_err(|_| "The audio thread is not running".to_string())
+    }
 }
 
 pub struct DesktopAudioHandler {
-    sender: Sender<AudioCommand>
+    remote: DesktopAudioRemote,
+    events: Receiver<AudioEvent>,
+    repaint: Arc<Mutex<Option<egui::Context>>>,
+    thread: Option<JoinHandle<()>>,
 }
 
 impl DesktopAudioHandler {
+    /// Starts the audio thread on the sound card, or on the null backend
+    /// when `NOISETTE_AUDIO_BACKEND=null` is set.
     pub fn new() -> Self {
+        let kind = match std::env::var("NOISETTE_AUDIO_BACKEND").as_deref() {
+            Ok("null") => AudioBackendKind::Null,
+            _ => AudioBackendKind::Device,
+        };
+
+        Self::with_backend(kind)
+    }
+
+    pub fn with_backend(kind: AudioBackendKind) -> Self {
         let (tx, rx): (Sender<AudioCommand>, Receiver<AudioCommand>) = mpsc::channel();
+        let (events_tx, events_rx) = mpsc::channel();
+
+        let state = Arc::new(RwLock::new(PlaybackState::default()));
+        let repaint = Arc::new(Mutex::new(None));
+
+        let publisher = Publisher {
+            state: Arc::clone(&state),
+            events: events_tx,
+            repaint: Arc::clone(&repaint),
+        };
 
-        thread::spawn(move || {
-            let mut audio = DesktopAudio::new();
+        let thread = thread::spawn(move || {
+            let mut audio = DesktopEngine::new(kind);
+            let mut master = MasterVolume::default();
 
-            let mut last_cleanup = Instant::now();
+            let mut last_tick = Instant::now();
 
             loop {
                 let now = Instant::now();
-                if now.duration_since(last_cleanup) > Duration::from_secs(3) {
-                    audio.clean_finished_sinks();
-                    last_cleanup = now;
+                let elapsed = now.duration_since(last_tick);
+                if elapsed > TICK_INTERVAL {
+                    audio.tick(elapsed);
+                    last_tick = now;
                 }
 
-                let cmd = match rx.recv() {
-                    Err(error) => {
-                        println!("{error}");
+                let cmd = match rx.recv_timeout(TICK_INTERVAL) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        // Controlla se qualche suono e' finito nel frattempo
+                        publisher.sync(&audio, master);
                         continue;
-                    },
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
                     Ok(cmd) => cmd
                 };
 
                 match cmd {
-                    AudioCommand::Play(sound) => audio.play(&sound),
-                    AudioCommand::Stop(sound) => audio.stop(&sound),
-                    AudioCommand::StopAll => audio.stop_all(),
-                    AudioCommand::IsPlaying(sender, sound) => {
-                        sender
-                            .send(audio.is_playing(sound))
-                            .map_err(|error| format!("{error:#?}"))
-                            .expect("Error occurred while sending is_playing signal:");
+                    AudioCommand::Play(sound, gain) => {
+                        if let Err(error) = audio.backend().play_with_gain(&sound, gain) {
+                            publisher.error(error);
+                        }
+                    }
+                    AudioCommand::Trigger(sound, gain, stop_on_new) => {
+                        if let Err(error) = audio.backend().trigger(&sound, gain, stop_on_new) {
+                            publisher.error(error);
+                        }
+                    }
+                    AudioCommand::Stop(sound) => audio.backend().stop(&sound),
+                    AudioCommand::StopAll => audio.backend().stop_all(),
+                    AudioCommand::SetDevice(device) => audio.set_device(device),
+                    AudioCommand::SetBackend(kind) => {
+                        // Chiude il file prima di buttare via il backend
+                        audio.stop_recording();
+                        audio.backend().stop_all();
+                        audio = DesktopEngine::new(kind);
+                        audio.set_master_gain(master.gain());
+                    }
+                    AudioCommand::StartRecording(target) => {
+                        if let Err(error) = audio.start_recording(target) {
+                            publisher.error(error);
+                        }
+                    }
+                    AudioCommand::StopRecording => audio.stop_recording(),
+                    AudioCommand::SetVolume(volume) => {
+                        master.set_volume(volume);
+                        audio.set_master_gain(master.gain());
+                    }
+                    AudioCommand::NudgeVolume(step) => {
+                        master.set_volume(master.volume + step);
+                        audio.set_master_gain(master.gain());
+                    }
+                    AudioCommand::ToggleMute => {
+                        master.muted = !master.muted;
+                        audio.set_master_gain(master.gain());
+                    }
+                    AudioCommand::Shutdown => {
+                        audio.stop_recording();
+                        break;
                     }
-                    AudioCommand::SetDevice(device) => audio.set_device(device)
                 }
+
+                publisher.sync(&audio, master);
             }
         });
 
-        Self {  sender: tx }
+        Self {
+            remote: DesktopAudioRemote { sender: tx, state },
+            events: events_rx,
+            repaint,
+            thread: Some(thread),
+        }
+    }
+
+    /// Stops the audio thread and waits for it, so an ongoing recording is
+    /// finalized before the process exits.
+    pub fn shutdown(&mut self) {
+        let _ = self.remote.sender.send(AudioCommand::Shutdown);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Handle for threads that need to drive playback without locking the handler.
+    pub fn remote(&self) -> DesktopAudioRemote {
+        self.remote.clone()
+    }
+
+    /// Context to repaint whenever the playback state changes.
+    pub fn set_repaint_context(&mut self, ctx: egui::Context) {
+        if let Ok(mut repaint) = self.repaint.lock() {
+            *repaint = Some(ctx);
+        }
+    }
+
+    /// Events published by the audio thread since the last call.
+    pub fn poll_events(&self) -> Vec<AudioEvent> {
+        self.events.try_iter().collect()
+    }
+
+    pub fn state(&self) -> PlaybackState {
+        self.remote.state()
+    }
+
+    pub fn set_backend(&mut self, kind: AudioBackendKind) {
+        self.remote.set_backend(kind);
     }
 
     pub fn set_device(&mut self, device: Option<String>) {
-        let _ = self.sender.send(AudioCommand::SetDevice(device));
+        self.remote.set_device(device);
+    }
+
+    pub fn start_recording(&mut self, target: RecordingTarget) {
+        self.remote.start_recording(target);
+    }
+
+    pub fn stop_recording(&mut self) {
+        self.remote.stop_recording();
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.remote.is_recording()
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.remote.set_volume(volume);
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.remote.toggle_mute();
     }
 }
 
 impl AudioBackend for DesktopAudioHandler {
-    fn play(&mut self, sound: &Sound) {
-        let _ = self.sender.send(AudioCommand::Play(sound.clone()));
+    fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String> {
+        self.remote.play_with_gain(sound, gain)
     }
 
     fn stop(&mut self, sound: &Sound) {
-        let _ = self.sender.send(AudioCommand::Stop(sound.clone()));
+        self.remote.stop(sound);
     }
 
     fn stop_all(&mut self) {
-        let _ = self.sender.send(AudioCommand::StopAll);
+        self.remote.stop_all();
     }
 
-    fn is_playing(&self, sound: Option<Sound>) -> bool {
-        let (resp_tx, resp_rx) = mpsc::channel();
+    fn is_playing(&self, sound: Option<&Sound>) -> bool {
+        self.remote.is_playing(sound)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::synth::{Generator, Waveform};
+    use crate::sound::RetriggerPolicy;
 
-        // Manda richiesta con il canale di risposta
-        if self.sender.send(AudioCommand::IsPlaying(resp_tx, sound)).is_err() {
-            // thread audio chiuso o errore
-            return false;
+    use super::*;
+
+    /// Events of the audio thread, waiting up to a second for `count` of them.
+    fn wait_for_events(handler: &DesktopAudioHandler, count: usize) -> Vec<AudioEvent> {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let mut events = Vec::new();
+        while events.len() < count && Instant::now() < deadline {
+            events.extend(handler.poll_events());
+            thread::sleep(Duration::from_millis(5));
         }
+        events
+    }
 
-        // Aspetta la risposta con un timeout ragionevole (es. 300ms)
-        match resp_rx.recv() {
-            Ok(is_playing) => is_playing,
-            Err(_) => false, // timeout o errore -> assumiamo non in riproduzione
+    fn synth(generator: Generator) -> Sound {
+        Sound {
+            generator: Some(generator),
+            editing: false,
+            ..Sound::default()
         }
     }
-}
\ No newline at end of file
+
+    /// A publisher on the null backend, as the audio thread would drive it.
+    fn null_engine() -> (Publisher, Receiver<AudioEvent>, DesktopEngine) {
+        let (events, receiver) = mpsc::channel();
+        let publisher = Publisher {
+            state: Arc::default(),
+            events,
+            repaint: Arc::default(),
+        };
+        (publisher, receiver, DesktopEngine::Null(NullAudio::new()))
+    }
+
+    #[test]
+    fn the_state_follows_play_stop_and_finish() {
+        let (publisher, events, mut engine) = null_engine();
+        let master = MasterVolume::default();
+        let (tone, noise) = (synth(Generator::default_tone()), synth(Generator::default_noise()));
+        let short = synth(Generator::Tone {
+            waveform: Waveform::Sine,
+            frequency: 440.0,
+            duration_ms: 20,
+        });
+
+        engine.backend().play(&tone).unwrap();
+        publisher.sync(&engine, master);
+        engine.backend().play(&noise).unwrap();
+        engine.backend().play(&short).unwrap();
+        publisher.sync(&engine, master);
+        assert!(publisher.state.read().unwrap().is_playing(Some(&noise)));
+
+        engine.backend().stop(&tone);
+        publisher.sync(&engine, master);
+        // Finito da solo, senza comandi
+        engine.tick(Duration::from_millis(50));
+        publisher.sync(&engine, master);
+
+        let state = publisher.state.read().unwrap().clone();
+        assert_eq!(state.playing, HashSet::from([noise.key().unwrap()]));
+
+        let mut received: Vec<AudioEvent> = events.try_iter().collect();
+        // Nello stesso giro l'ordine tra suoni diversi non conta
+        received[1..3].sort_by_key(|event| format!("{event:?}"));
+        let mut started_together = vec![
+            AudioEvent::Started(noise.key().unwrap()),
+            AudioEvent::Started(short.key().unwrap()),
+        ];
+        started_together.sort_by_key(|event| format!("{event:?}"));
+        assert_eq!(received[0], AudioEvent::Started(tone.key().unwrap()));
+        assert_eq!(received[1..3], started_together);
+        assert_eq!(
+            received[3..],
+            [AudioEvent::Finished(tone.key().unwrap()), AudioEvent::Finished(short.key().unwrap())]
+        );
+    }
+
+    #[test]
+    fn nothing_is_published_without_changes() {
+        let (publisher, events, mut engine) = null_engine();
+        let master = MasterVolume::default();
+
+        publisher.sync(&engine, master);
+        engine.backend().play(&synth(Generator::default_tone())).unwrap();
+        publisher.sync(&engine, master);
+        publisher.sync(&engine, master);
+
+        assert_eq!(events.try_iter().count(), 1);
+
+        // Il volume cambia lo stato ma non e' un evento
+        let quieter = MasterVolume { volume: 0.5, muted: false };
+        publisher.sync(&engine, quieter);
+        assert_eq!(publisher.state.read().unwrap().master, quieter);
+        assert_eq!(events.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn errors_reach_the_ui_in_order() {
+        let mut handler = DesktopAudioHandler::with_backend(AudioBackendKind::Null);
+        let tone = synth(Generator::default_tone());
+        let missing = Sound {
+            path: Some("/nonexistent/noisette.wav".to_string()),
+            editing: false,
+            ..Sound::default()
+        };
+
+        handler.remote().play(&tone).unwrap();
+        handler.remote().play(&missing).unwrap();
+        handler.remote().stop(&tone);
+
+        let events = wait_for_events(&handler, 3);
+        handler.shutdown();
+        assert_eq!(
+            events,
+            [
+                AudioEvent::Started(tone.key().unwrap()),
+                AudioEvent::Error("Sound file not found: /nonexistent/noisette.wav".to_string()),
+                AudioEvent::Finished(tone.key().unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn quick_presses_see_the_state_of_the_audio_thread() {
+        let mut handler = DesktopAudioHandler::with_backend(AudioBackendKind::Null);
+        let mut remote = handler.remote();
+        let sound = Sound {
+            generator: Some(Generator::default_tone()),
+            retrigger: RetriggerPolicy::Stop,
+            editing: false,
+            ..Sound::default()
+        };
+        let key = sound.key().unwrap();
+
+        // La seconda pressione arriva prima che lo stato venga pubblicato
+        remote.trigger(&sound, 1.0, true).unwrap();
+        remote.trigger(&sound, 1.0, true).unwrap();
+
+        let events = wait_for_events(&handler, 2);
+        handler.shutdown();
+        assert_eq!(events, [AudioEvent::Started(key.clone()), AudioEvent::Finished(key)]);
+        assert!(!handler.state().is_playing(None));
+    }
+}
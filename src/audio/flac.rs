@@ -0,0 +1,116 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use flacenc::bitsink::ByteSink;
+use flacenc::component::{BitRepr, StreamInfo};
+use flacenc::config;
+use flacenc::error::{Verified, Verify};
+use flacenc::source::{Context, Fill, FrameBuf};
+
+/// Samples per channel in each FLAC frame, the reference encoder's default.
+const BLOCK_SIZE: usize = 4096;
+
+const BITS_PER_SAMPLE: usize = 24;
+
+/// Where the STREAMINFO block starts, right after the `fLaC` marker.
+const STREAM_INFO_OFFSET: u64 = 4;
+
+/// Encodes float samples to a FLAC file as they arrive, so a long session
+/// is never held in memory. The header is only complete after [`finalize`].
+///
+/// [`finalize`]: FlacWriter::finalize
+pub struct FlacWriter {
+    file: BufWriter<File>,
+    config: Verified<config::Encoder>,
+    stream_info: StreamInfo,
+    framebuf: FrameBuf,
+    context: Context,
+    /// Interleaved samples waiting for a whole block.
+    pending: Vec<i32>,
+    channels: usize,
+}
+
+impl FlacWriter {
+    pub fn create(path: &Path, channels: u16, sample_rate: u32) -> Result<Self, String> {
+        let channels = usize::from(channels);
+        let stream_info = StreamInfo::new(sample_rate as usize, channels, BITS_PER_SAMPLE).map_err(|error| error.to_string())?;
+        let config = config::Encoder::default()
+            .into_verified()
+            .map_err(|(_, error)| error.to_string())?;
+        let framebuf = FrameBuf::with_size(channels, BLOCK_SIZE).map_err(|error| error.to_string())?;
+
+        let file = File::create(path).map_err(|error| error.to_string())?;
+        let mut writer = Self {
+            file: BufWriter::new(file),
+            config,
+            stream_info,
+            framebuf,
+            context: Context::new(BITS_PER_SAMPLE, channels),
+            pending: Vec::with_capacity(BLOCK_SIZE * channels),
+            channels,
+        };
+
+        writer.file.write_all(b"fLaC").map_err(|error| error.to_string())?;
+        writer.write_stream_info()?;
+        Ok(writer)
+    }
+
+    pub fn write_samples(&mut self, samples: &[f32]) -> Result<(), String> {
+        let full_scale = ((1 << (BITS_PER_SAMPLE - 1)) - 1) as f32;
+        self.pending.extend(samples.iter().map(|sample| (sample.clamp(-1.0, 1.0) * full_scale) as i32));
+
+        let block_len = BLOCK_SIZE * self.channels;
+        while self.pending.len() >= block_len {
+            let block: Vec<i32> = self.pending.drain(..block_len).collect();
+            self.encode_block(&block)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes what is left and writes the final header: length, frame
+    /// sizes and checksum.
+    pub fn finalize(mut self) -> Result<(), String> {
+        // Solo frame completi: un campione spaiato verrebbe scartato dal decoder
+        let tail_len = self.pending.len() - self.pending.len() % self.channels;
+        if tail_len > 0 {
+            let tail: Vec<i32> = self.pending.drain(..tail_len).collect();
+            self.encode_block(&tail)?;
+        }
+
+        // Come l'encoder di riferimento: l'ultimo frame, piu' corto, non conta
+        self.stream_info
+            .set_block_sizes(BLOCK_SIZE, BLOCK_SIZE)
+            .map_err(|error| error.to_string())?;
+        self.stream_info.set_md5_digest(&self.context.md5_digest());
+        self.file.seek(SeekFrom::Start(STREAM_INFO_OFFSET)).map_err(|error| error.to_string())?;
+        self.write_stream_info()?;
+        self.file.flush().map_err(|error| error.to_string())
+    }
+
+    fn encode_block(&mut self, interleaved: &[i32]) -> Result<(), String> {
+        (&mut self.framebuf, &mut self.context)
+            .fill_interleaved(interleaved)
+            .map_err(|error| error.to_string())?;
+        let frame_number = self.context.current_frame_number().unwrap_or_default();
+
+        let frame = flacenc::encode_fixed_size_frame(&self.config, &self.framebuf, frame_number, &self.stream_info)
+            .map_err(|error| error.to_string())?;
+        self.stream_info.update_frame_info(&frame);
+
+        let mut sink = ByteSink::new();
+        frame.write(&mut sink).map_err(|error| error.to_string())?;
+        self.file.write_all(sink.as_slice()).map_err(|error| error.to_string())
+    }
+
+    /// The only metadata block: last-block flag, type 0 and its 34 bytes.
+    fn write_stream_info(&mut self) -> Result<(), String> {
+        let mut sink = ByteSink::new();
+        self.stream_info.write(&mut sink).map_err(|error| error.to_string())?;
+        let body = sink.as_slice();
+
+        let length = (body.len() as u32).to_be_bytes();
+        self.file.write_all(&[0x80, length[1], length[2], length[3]]).map_err(|error| error.to_string())?;
+        self.file.write_all(body).map_err(|error| error.to_string())
+    }
+}
@@ -1,10 +1,96 @@
+use std::collections::HashSet;
+
 use crate::Sound;
+use crate::sound::RetriggerPolicy;
+
+/// Master volume of the output, applied on top of every sound.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MasterVolume {
+    /// Between 0 and 1.
+    pub volume: f32,
+    pub muted: bool,
+}
+
+impl Default for MasterVolume {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl MasterVolume {
+    pub fn gain(&self) -> f32 {
+        if self.muted { 0.0 } else { self.volume }
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+}
 
+/// Snapshot of what the audio backend is doing, readable by the UI without
+/// waiting on the audio thread.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlaybackState {
+    /// [`Sound::key`] of every sound currently playing.
+    pub playing: HashSet<String>,
+    pub recording: bool,
+    pub master: MasterVolume,
+}
 
+impl PlaybackState {
+    pub fn is_playing(&self, sound: Option<&Sound>) -> bool {
+        match sound {
+            Some(sound) => sound.key().is_some_and(|key| self.playing.contains(&key)),
+            None => !self.playing.is_empty(),
+        }
+    }
+}
+
+/// Published by the audio thread whenever something changes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AudioEvent {
+    Started(String),
+    Finished(String),
+    Error(String),
+}
 
 pub trait AudioBackend {
-    fn play(&mut self, sound : &Sound);
+    fn play(&mut self, sound : &Sound) -> Result<(), String> {
+        self.play_with_gain(sound, 1.0)
+    }
+    /// Plays `sound` scaled by `gain`, e.g. from a MIDI velocity.
+    fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String>;
     fn stop(&mut self, sound : &Sound);
     fn stop_all(&mut self);
-    fn is_playing(&self, sound: Option<Sound>) -> bool;
+    fn is_playing(&self, sound: Option<&Sound>) -> bool;
+
+    /// Plays `sound` for a shortcut: its [`RetriggerPolicy`] decides what
+    /// happens if it is already playing, `stop_on_new` whether it interrupts
+    /// the other sounds or gives way to them.
+    fn trigger(&mut self, sound: &Sound, gain: f32, stop_on_new: bool) -> Result<(), String> {
+        if self.is_playing(Some(sound)) {
+            // Il suono e' gia' in riproduzione: decide la sua politica
+            match sound.retrigger {
+                RetriggerPolicy::Restart => self.stop(sound),
+                RetriggerPolicy::Ignore => return Ok(()),
+                RetriggerPolicy::Stack => {}
+                RetriggerPolicy::Stop => {
+                    self.stop(sound);
+                    return Ok(());
+                }
+            }
+        } else if self.is_playing(None) {
+            // C'e' un altro suono in riproduzione
+            if stop_on_new {
+                self.stop_all();
+            } else {
+                return Ok(());
+            }
+        }
+
+        self.play_with_gain(sound, gain)
+    }
 }
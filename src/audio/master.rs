@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use rodio::Source;
+
+/// Gain shared between the audio thread and the output stream, stored as the
+/// bits of an `f32` so the stream never has to take a lock.
+#[derive(Clone)]
+pub struct SharedGain(Arc<AtomicU32>);
+
+impl SharedGain {
+    pub fn new(gain: f32) -> Self {
+        Self(Arc::new(AtomicU32::new(gain.to_bits())))
+    }
+
+    pub fn get(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, gain: f32) {
+        self.0.store(gain.to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Source wrapper that scales the whole mix by the master gain.
+pub struct MasterGain<S> {
+    input: S,
+    gain: SharedGain,
+}
+
+impl<S> MasterGain<S> {
+    pub fn new(input: S, gain: SharedGain) -> Self {
+        Self { input, gain }
+    }
+}
+
+impl<S> Iterator for MasterGain<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        self.input.next().map(|sample| sample * self.gain.get())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<S> Source for MasterGain<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
@@ -1,15 +1,90 @@
 pub mod interface;
 
+pub mod synth;
+
+/// Where the audio thread sends its output.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AudioBackendKind {
+    /// The selected sound card.
+    #[default]
+    Device,
+    /// No sound card: sounds are mixed in memory and discarded.
+    Null,
+}
+
+/// The file format of the session recordings.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RecordingFormat {
+    /// 32-bit float, exactly what was mixed.
+    #[default]
+    Wav,
+    /// 24-bit lossless, about half the size.
+    Flac,
+}
+
+impl RecordingFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            RecordingFormat::Wav => "wav",
+            RecordingFormat::Flac => "flac",
+        }
+    }
+}
+
+/// Where and in which format the session is recorded.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecordingTarget {
+    pub directory: std::path::PathBuf,
+    pub format: RecordingFormat,
+}
+
+/// File extensions accepted by the file pickers and the decoder.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "mp3", "wav", "flac", "ogg", "oga", "opus", "m4a", "mp4", "aac", "aif", "aiff",
+];
+
+/// Returns the MIME type used when handing a sound file to the browser.
+pub fn mime_type(path: &str) -> &'static str {
+    let extension = std::path::Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+
+    match extension.as_deref() {
+        Some("mp3") => "audio/mpeg",
+        Some("flac") => "audio/flac",
+        Some("ogg") | Some("oga") | Some("opus") => "audio/ogg",
+        Some("m4a") | Some("mp4") => "audio/mp4",
+        Some("aac") => "audio/aac",
+        Some("aif") | Some("aiff") => "audio/aiff",
+        _ => "audio/wav",
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod desktop;
 #[cfg(not(target_arch = "wasm32"))]
 pub use desktop::DesktopAudioHandler as PlatformAudioHandler;
 #[cfg(not(target_arch = "wasm32"))]
 pub use desktop::{get_default_output_device, get_output_devices, get_device_from_name};
+#[cfg(not(target_arch = "wasm32"))]
+pub mod master;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod null;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod flac;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod opus;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod recorder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use recorder::default_recordings_dir;
 
 #[cfg(target_arch = "wasm32")]
 pub mod web;
 #[cfg(target_arch = "wasm32")]
 pub use web::WebAudio as PlatformAudioHandler;
 #[cfg(target_arch = "wasm32")]
-pub use web::{get_default_output_device, get_output_devices, get_device_from_name};
+pub use web::{get_default_output_device, get_output_devices, get_device_from_name, default_recordings_dir};
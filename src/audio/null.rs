@@ -0,0 +1,297 @@
+use rodio::source::UniformSourceIterator;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::Sound;
+
+use super::desktop::decode_file;
+use crate::shortcut::trigger::TriggerTarget;
+
+use super::interface::AudioBackend;
+use super::synth::SYNTH_SAMPLE_RATE;
+
+/// Sample rate of the captured mix, mono.
+pub const CAPTURE_SAMPLE_RATE: u32 = SYNTH_SAMPLE_RATE;
+
+/// How many [`PlaybackRecord`]s are kept; older ones are dropped.
+pub const HISTORY_LIMIT: usize = 1024;
+
+/// One sound played by the [`NullAudio`] backend.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlaybackRecord {
+    pub key: String,
+    pub started_at: Duration,
+    /// `None` while the sound is still playing.
+    pub stopped_at: Option<Duration>,
+    pub gain: f32,
+}
+
+struct Voice {
+    key: String,
+    /// Index of the record among every sound ever started.
+    record: usize,
+    samples: Vec<f32>,
+    position: usize,
+    gain: f32,
+}
+
+/// Audio backend that needs no sound card: sounds are mixed into an
+/// in-memory buffer and time only moves forward when [`NullAudio::advance`]
+/// is called.
+pub struct NullAudio {
+    clock: Duration,
+    master_gain: f32,
+    voices: Vec<Voice>,
+    history: Vec<PlaybackRecord>,
+    /// Records dropped from the front of `history`.
+    dropped: usize,
+    output: Vec<f32>,
+}
+
+impl NullAudio {
+    pub fn new() -> Self {
+        Self {
+            clock: Duration::ZERO,
+            master_gain: 1.0,
+            voices: Vec::new(),
+            history: Vec::new(),
+            dropped: 0,
+            output: Vec::new(),
+        }
+    }
+
+    /// Current position of the virtual clock.
+    pub fn now(&self) -> Duration {
+        self.clock
+    }
+
+    pub fn set_master_gain(&mut self, gain: f32) {
+        self.master_gain = gain;
+    }
+
+    /// The last [`HISTORY_LIMIT`] sounds started, in start order.
+    pub fn history(&self) -> &[PlaybackRecord] {
+        &self.history
+    }
+
+    /// Mono mix rendered since the last [`NullAudio::take_output`].
+    pub fn output(&self) -> &[f32] {
+        &self.output
+    }
+
+    pub fn take_output(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.output)
+    }
+
+    /// Moves the virtual clock forward, mixing the playing sounds into the
+    /// output buffer and retiring the ones that end.
+    pub fn advance(&mut self, duration: Duration) {
+        let frames = (duration.as_secs_f64() * CAPTURE_SAMPLE_RATE as f64).round() as usize;
+        let start = self.output.len();
+        self.output.resize(start + frames, 0.0);
+
+        for voice in &mut self.voices {
+            let available = voice.samples.len() - voice.position;
+            let count = available.min(frames);
+
+            for (i, sample) in voice.samples[voice.position..voice.position + count].iter().enumerate() {
+                self.output[start + i] += sample * voice.gain;
+            }
+
+            voice.position += count;
+
+            if voice.position >= voice.samples.len()
+                && let Some(record) = record_mut(&mut self.history, self.dropped, voice.record)
+            {
+                record.stopped_at = Some(self.clock + frames_to_duration(count));
+            }
+        }
+
+        self.voices.retain(|voice| voice.position < voice.samples.len());
+        self.clock += duration;
+    }
+
+    /// Keys of the sounds that are still playing.
+    pub fn playing_keys(&self) -> HashSet<String> {
+        self.voices.iter().map(|voice| voice.key.clone()).collect()
+    }
+
+    fn render(sound: &Sound) -> Result<Vec<f32>, String> {
+        if let Some(generator) = &sound.generator {
+            return Ok(generator.render());
+        }
+
+        let source = decode_file(sound.path.as_deref().ok_or("Sound path is None!")?)?;
+        Ok(UniformSourceIterator::<_, f32>::new(source, 1, CAPTURE_SAMPLE_RATE).collect())
+    }
+
+    fn stop_key(&mut self, key: &str) {
+        let clock = self.clock;
+        let (history, dropped) = (&mut self.history, self.dropped);
+
+        self.voices.retain(|voice| {
+            if voice.key != key {
+                return true;
+            }
+            if let Some(record) = record_mut(history, dropped, voice.record) {
+                record.stopped_at = Some(clock);
+            }
+            false
+        });
+    }
+}
+
+impl Default for NullAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioBackend for NullAudio {
+    fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String> {
+        let key = sound.key().ok_or("Sound path is None!")?;
+        let samples = Self::render(sound)?;
+        let gain = self.master_gain * gain;
+
+        self.voices.push(Voice {
+            key: key.clone(),
+            record: self.dropped + self.history.len(),
+            samples,
+            position: 0,
+            gain,
+        });
+
+        self.history.push(PlaybackRecord {
+            key,
+            started_at: self.clock,
+            stopped_at: None,
+            gain,
+        });
+
+        // Sessioni lunghe: la storia non deve crescere senza limite
+        if self.history.len() > HISTORY_LIMIT {
+            let excess = self.history.len() - HISTORY_LIMIT;
+            self.history.drain(..excess);
+            self.dropped += excess;
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self, sound: &Sound) {
+        if let Some(key) = sound.key() {
+            self.stop_key(&key);
+        }
+    }
+
+    fn stop_all(&mut self) {
+        for voice in self.voices.drain(..) {
+            if let Some(record) = record_mut(&mut self.history, self.dropped, voice.record) {
+                record.stopped_at = Some(self.clock);
+            }
+        }
+    }
+
+    fn is_playing(&self, sound: Option<&Sound>) -> bool {
+        match sound {
+            Some(sound) => sound
+                .key()
+                .is_some_and(|key| self.voices.iter().any(|voice| voice.key == key)),
+            None => !self.voices.is_empty(),
+        }
+    }
+}
+
+/// The record of the `index`-th sound started, unless it was dropped.
+fn record_mut(history: &mut [PlaybackRecord], dropped: usize, index: usize) -> Option<&mut PlaybackRecord> {
+    history.get_mut(index.checked_sub(dropped)?)
+}
+
+/// Lets the trigger pipeline run headless. There is nothing to record and
+/// no mixer: recording, mute and volume do nothing.
+impl TriggerTarget for NullAudio {
+    fn is_recording(&self) -> bool {
+        false
+    }
+
+    fn start_recording(&mut self, _target: super::RecordingTarget) {}
+
+    fn stop_recording(&mut self) {}
+
+    fn toggle_mute(&mut self) {}
+
+    fn nudge_volume(&mut self, _step: f32) {}
+}
+
+fn frames_to_duration(frames: usize) -> Duration {
+    Duration::from_secs_f64(frames as f64 / CAPTURE_SAMPLE_RATE as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::synth::{Generator, Waveform};
+
+    fn tone(duration_ms: u32) -> Sound {
+        Sound {
+            generator: Some(Generator::Tone { waveform: Waveform::Square, frequency: 440.0, duration_ms }),
+            ..Sound::default()
+        }
+    }
+
+    #[test]
+    fn plays_mixes_and_retires_sounds() {
+        let mut audio = NullAudio::new();
+        let sound = tone(100);
+        let backend: &mut dyn AudioBackend = &mut audio;
+
+        backend.play_with_gain(&sound, 0.5).unwrap();
+        assert!(backend.is_playing(Some(&sound)));
+
+        audio.advance(Duration::from_millis(50));
+        assert!(audio.is_playing(None));
+        assert_eq!(audio.output().len(), CAPTURE_SAMPLE_RATE as usize / 20);
+        assert!(audio.output().iter().any(|sample| *sample != 0.0));
+
+        audio.advance(Duration::from_millis(100));
+        assert!(!audio.is_playing(None));
+
+        let record = &audio.history()[0];
+        assert_eq!(record.key, sound.key().unwrap());
+        assert_eq!(record.started_at, Duration::ZERO);
+        assert_eq!(record.gain, 0.5);
+        assert!(record.stopped_at.is_some_and(|at| at.abs_diff(Duration::from_millis(100)) < Duration::from_millis(1)));
+    }
+
+    #[test]
+    fn stop_records_the_clock() {
+        let mut audio = NullAudio::new();
+        let sound = tone(1000);
+
+        audio.play(&sound).unwrap();
+        audio.advance(Duration::from_millis(200));
+        audio.stop(&sound);
+
+        assert!(audio.playing_keys().is_empty());
+        assert_eq!(audio.history()[0].stopped_at, Some(Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut audio = NullAudio::new();
+        let long = tone(1000);
+        audio.play(&long).unwrap();
+
+        let short = tone(10);
+        for _ in 0..HISTORY_LIMIT {
+            audio.play(&short).unwrap();
+        }
+        assert_eq!(audio.history().len(), HISTORY_LIMIT);
+        assert!(audio.history().iter().all(|record| record.key == short.key().unwrap()));
+
+        // La voce il cui record e' stato scartato continua a suonare
+        audio.advance(Duration::from_millis(20));
+        assert_eq!(audio.playing_keys(), HashSet::from([long.key().unwrap()]));
+        assert!(audio.history().iter().all(|record| record.stopped_at.is_some()));
+    }
+}
@@ -0,0 +1,196 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+use ogg::PacketReader;
+use opus_decoder::OpusDecoder;
+use rodio::buffer::SamplesBuffer;
+
+/// Opus always decodes at 48 kHz, whatever rate the source had.
+pub const OPUS_SAMPLE_RATE: u32 = 48_000;
+
+/// The longest Opus packet, 120 ms, in samples per channel.
+const MAX_PACKET_FRAMES: usize = 5760;
+
+/// The identification header that opens every Ogg Opus stream.
+struct OpusHead {
+    channels: usize,
+    /// Decoder warm-up samples to drop from the start.
+    pre_skip: usize,
+    /// Gain in dB, Q7.8, to apply to the decoded output.
+    output_gain: i16,
+}
+
+impl OpusHead {
+    fn parse(packet: &[u8]) -> Result<Self, String> {
+        if packet.len() < 19 || !packet.starts_with(b"OpusHead") {
+            return Err("missing Opus header".to_string());
+        }
+
+        let channels = usize::from(packet[9]);
+        let mapping_family = packet[18];
+        // Oltre lo stereo servirebbe il decoder multistream
+        if mapping_family != 0 || !(1..=2).contains(&channels) {
+            return Err("only mono and stereo Opus files are supported".to_string());
+        }
+
+        Ok(Self {
+            channels,
+            pre_skip: usize::from(u16::from_le_bytes([packet[10], packet[11]])),
+            output_gain: i16::from_le_bytes([packet[16], packet[17]]),
+        })
+    }
+
+    fn gain(&self) -> f32 {
+        10f32.powf(f32::from(self.output_gain) / (20.0 * 256.0))
+    }
+}
+
+/// Whether the file is an Ogg stream carrying Opus, whatever its extension.
+pub fn is_ogg_opus(path: &Path) -> bool {
+    // Pagina Ogg di 27 byte, un solo segmento, poi il pacchetto OpusHead
+    let mut header = [0u8; 36];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|()| header.starts_with(b"OggS") && &header[28..36] == b"OpusHead")
+}
+
+/// Decodes a whole Ogg Opus file, trimmed to the length its last page
+/// declares.
+pub fn decode(path: &Path) -> Result<SamplesBuffer<f32>, String> {
+    let file = File::open(path).map_err(|error| error.to_string())?;
+    let mut reader = PacketReader::new(BufReader::new(file));
+    let mut next_packet = || reader.read_packet().map_err(|error| format!("corrupted Ogg stream ({error})"));
+
+    let first = next_packet()?.ok_or("empty Ogg stream")?;
+    let serial = first.stream_serial();
+    let head = OpusHead::parse(&first.data)?;
+
+    let mut decoder = OpusDecoder::new(OPUS_SAMPLE_RATE, head.channels).map_err(|error| error.to_string())?;
+    let mut frame = vec![0f32; MAX_PACKET_FRAMES * head.channels];
+    let mut samples = Vec::new();
+    let mut granule = None;
+
+    // Il secondo pacchetto sono i tag: nessun audio
+    let mut skipped_tags = false;
+    while let Some(packet) = next_packet()? {
+        // Gli altri stream multiplexati non ci riguardano
+        if packet.stream_serial() != serial {
+            continue;
+        }
+        if !skipped_tags {
+            skipped_tags = true;
+            continue;
+        }
+
+        let decoded = decoder
+            .decode_float(&packet.data, &mut frame, false)
+            .map_err(|error| format!("corrupted Opus packet ({error})"))?;
+        samples.extend_from_slice(&frame[..decoded * head.channels]);
+
+        if packet.last_in_page() {
+            granule = Some(packet.absgp_page());
+        }
+        if packet.last_in_stream() {
+            break;
+        }
+    }
+
+    // La granule position conta anche il pre-skip e taglia il padding finale
+    let total_frames = samples.len() / head.channels;
+    let end = granule
+        .and_then(|granule| usize::try_from(granule).ok())
+        .map_or(total_frames, |granule| granule.min(total_frames));
+    let start = head.pre_skip.min(end);
+
+    let gain = head.gain();
+    let samples = samples[start * head.channels..end * head.channels]
+        .iter()
+        .map(|sample| sample * gain)
+        .collect::<Vec<_>>();
+
+    Ok(SamplesBuffer::new(head.channels as u16, OPUS_SAMPLE_RATE, samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::TAU;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use ogg::writing::{PacketWriteEndInfo, PacketWriter};
+    use opus_rs::{Application, OpusEncoder};
+    use rodio::Source;
+
+    use super::*;
+
+    const PRE_SKIP: u16 = 312;
+    const FRAME: usize = 960;
+
+    /// Encodes `frames` of a stereo sine into an Ogg Opus file.
+    fn write_sine(path: &Path, frames: usize) {
+        let mut encoder = OpusEncoder::new(OPUS_SAMPLE_RATE as i32, 2, Application::Audio).unwrap();
+        let mut writer = PacketWriter::new(File::create(path).unwrap());
+
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 2]);
+        head.extend_from_slice(&PRE_SKIP.to_le_bytes());
+        head.extend_from_slice(&OPUS_SAMPLE_RATE.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+        writer.write_packet(head.into_boxed_slice(), 7, PacketWriteEndInfo::EndPage, 0).unwrap();
+
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&[0; 8]);
+        writer.write_packet(tags.into_boxed_slice(), 7, PacketWriteEndInfo::EndPage, 0).unwrap();
+
+        // Il pre-skip sposta l'audio: serve un pacchetto in piu' per coprirlo
+        let packets = (frames + usize::from(PRE_SKIP)).div_ceil(FRAME);
+        let mut output = [0u8; 1276];
+        for index in 0..packets {
+            let pcm: Vec<f32> = (index * FRAME..(index + 1) * FRAME)
+                .flat_map(|frame| {
+                    let sample = (TAU * 440.0 * frame as f32 / OPUS_SAMPLE_RATE as f32).sin() * 0.5;
+                    [sample, sample]
+                })
+                .collect();
+            let len = encoder.encode(&pcm, FRAME, &mut output).unwrap();
+
+            let last = index + 1 == packets;
+            let end = if last { PacketWriteEndInfo::EndStream } else { PacketWriteEndInfo::EndPage };
+            let granule = if last { frames + usize::from(PRE_SKIP) } else { (index + 1) * FRAME };
+            writer.write_packet(output[..len].into(), 7, end, granule as u64).unwrap();
+        }
+    }
+
+    fn temp_file(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("noisette-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn decodes_to_the_declared_length() {
+        let path = temp_file("sine.opus");
+        write_sine(&path, 10_000);
+
+        assert!(is_ogg_opus(&path));
+        let source = decode(&path).unwrap();
+        assert_eq!(source.channels(), 2);
+        assert_eq!(source.sample_rate(), OPUS_SAMPLE_RATE);
+
+        let samples: Vec<f32> = source.collect();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(samples.len(), 2 * 10_000);
+        let peak = samples.iter().fold(0f32, |peak, sample| peak.max(sample.abs()));
+        assert!((0.3..0.7).contains(&peak), "peak {peak}");
+    }
+
+    #[test]
+    fn other_files_are_not_opus() {
+        let path = temp_file("not-opus.ogg");
+        fs::write(&path, b"OggS but not really an Opus stream at all").unwrap();
+
+        assert!(!is_ogg_opus(&path));
+        assert!(decode(&path).is_err());
+        let _ = fs::remove_file(&path);
+    }
+}
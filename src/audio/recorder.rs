@@ -0,0 +1,455 @@
+use rodio::Source;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::{RecordingFormat, RecordingTarget};
+use super::flac::FlacWriter;
+
+/// Frames accumulated by the tap before being handed to the writer thread.
+const CHUNK_FRAMES: usize = 1024;
+
+/// How long `stop` waits for the tap to hand over the last samples. The
+/// output may be stalled, e.g. by an unplugged device.
+const CLOSE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Shared slot holding the channel of the active recording, if any.
+#[derive(Clone, Default)]
+pub struct RecorderSlot {
+    sender: Arc<Mutex<Option<Sender<Vec<f32>>>>>,
+    /// Asks the tap to send what it has buffered and close the channel.
+    closing: Arc<AtomicBool>,
+}
+
+/// Source wrapper that forwards the mixed signal to the output unchanged
+/// while copying it to the active recording.
+pub struct RecordingTap<S> {
+    input: S,
+    slot: RecorderSlot,
+    buffer: Vec<f32>,
+    /// Samples handed over at a time; the buffer grows past it while the
+    /// slot is busy.
+    chunk_len: usize,
+}
+
+impl<S> RecordingTap<S>
+where
+    S: Source<Item = f32>,
+{
+    pub fn new(input: S, slot: RecorderSlot) -> Self {
+        let chunk_len = CHUNK_FRAMES * input.channels() as usize;
+        Self {
+            input,
+            slot,
+            buffer: Vec::with_capacity(chunk_len),
+            chunk_len,
+        }
+    }
+
+    fn flush(&mut self) {
+        let closing = self.slot.closing.load(Ordering::Relaxed);
+
+        // Non bloccare mai il thread audio: se il lock e' occupato il blocco
+        // resta nel buffer e parte con il prossimo
+        let Ok(mut sender) = self.slot.sender.try_lock() else {
+            return;
+        };
+
+        if let Some(active) = sender.as_ref() {
+            let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(self.chunk_len));
+            let _ = active.send(chunk);
+        } else {
+            self.buffer.clear();
+        }
+
+        if closing {
+            // Chiudendo il canale il writer finalizza il file
+            sender.take();
+            self.slot.closing.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+impl<S> Iterator for RecordingTap<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = self.input.next()?;
+        self.buffer.push(sample);
+
+        // Si consegna sempre a frame completi, anche la coda in chiusura
+        let frame_end = self.buffer.len().is_multiple_of(self.input.channels().max(1) as usize);
+        if frame_end && (self.buffer.len() >= self.chunk_len || self.slot.closing.load(Ordering::Relaxed)) {
+            self.flush();
+        }
+
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+impl<S> Source for RecordingTap<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.input.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.input.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.input.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.input.total_duration()
+    }
+}
+
+/// The file a session is written to.
+enum SessionWriter {
+    Wav(hound::WavWriter<BufWriter<File>>),
+    Flac(Box<FlacWriter>),
+}
+
+impl SessionWriter {
+    fn create(path: &Path, format: RecordingFormat, channels: u16, sample_rate: u32) -> Result<Self, String> {
+        let writer = match format {
+            RecordingFormat::Wav => {
+                let spec = hound::WavSpec {
+                    channels,
+                    sample_rate,
+                    bits_per_sample: 32,
+                    sample_format: hound::SampleFormat::Float,
+                };
+                hound::WavWriter::create(path, spec).map(SessionWriter::Wav).map_err(|error| error.to_string())
+            }
+            RecordingFormat::Flac => FlacWriter::create(path, channels, sample_rate).map(|writer| SessionWriter::Flac(Box::new(writer))),
+        };
+
+        writer.map_err(|error| format!("Cannot create {}: {error}", path.display()))
+    }
+
+    fn write(&mut self, chunk: &[f32]) -> Result<(), String> {
+        match self {
+            SessionWriter::Wav(writer) => chunk
+                .iter()
+                .try_for_each(|sample| writer.write_sample(*sample))
+                .map_err(|error| error.to_string()),
+            SessionWriter::Flac(writer) => writer.write_samples(chunk),
+        }
+    }
+
+    fn finalize(self) -> Result<(), String> {
+        match self {
+            SessionWriter::Wav(writer) => writer.finalize().map_err(|error| error.to_string()),
+            SessionWriter::Flac(writer) => writer.finalize(),
+        }
+    }
+}
+
+/// Writes everything that goes through a [`RecordingTap`] to a WAV or FLAC
+/// file. Dropping it finalizes the file, like [`SessionRecorder::stop`].
+pub struct SessionRecorder {
+    slot: RecorderSlot,
+    path: Option<PathBuf>,
+    writer: Option<JoinHandle<()>>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl SessionRecorder {
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        Self {
+            slot: RecorderSlot::default(),
+            path: None,
+            writer: None,
+            channels,
+            sample_rate,
+        }
+    }
+
+    pub fn slot(&self) -> RecorderSlot {
+        self.slot.clone()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn start(&mut self, target: &RecordingTarget) -> Result<PathBuf, String> {
+        self.stop();
+
+        let directory = &target.directory;
+        fs::create_dir_all(directory)
+            .map_err(|error| format!("Cannot create {}: {error}", directory.display()))?;
+
+        let path = recording_path(directory, target.format);
+        let mut writer = SessionWriter::create(&path, target.format, self.channels, self.sample_rate)?;
+
+        let (tx, rx) = mpsc::channel::<Vec<f32>>();
+
+        let writer = thread::spawn(move || {
+            // Il canale si chiude quando la registrazione viene fermata
+            for chunk in rx {
+                if let Err(error) = writer.write(&chunk) {
+                    eprintln!("Error while writing the recording: {error}");
+                    return;
+                }
+            }
+
+            if let Err(error) = writer.finalize() {
+                eprintln!("Error while finalizing the recording: {error}");
+            }
+        });
+
+        if let Ok(mut sender) = self.slot.sender.lock() {
+            *sender = Some(tx);
+        }
+
+        self.path = Some(path.clone());
+        self.writer = Some(writer);
+        Ok(path)
+    }
+
+    /// Stops the recording and returns its path once the file is complete.
+    pub fn stop(&mut self) -> Option<PathBuf> {
+        let path = self.path.take()?;
+
+        // Il tap consegna gli ultimi campioni e chiude il canale
+        self.slot.closing.store(true, Ordering::Relaxed);
+        let deadline = Instant::now() + CLOSE_TIMEOUT;
+        while self.slot.sender.lock().is_ok_and(|sender| sender.is_some()) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        // L'uscita non avanza: si chiude senza la coda
+        if let Ok(mut sender) = self.slot.sender.lock() {
+            sender.take();
+        }
+        self.slot.closing.store(false, Ordering::Relaxed);
+
+        if let Some(writer) = self.writer.take() && writer.join().is_err() {
+            eprintln!("The recording writer panicked");
+        }
+
+        Some(path)
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+pub fn default_recordings_dir() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_default()
+        .join("recordings")
+}
+
+/// A new file in `directory`, named after the current time in milliseconds
+/// and numbered if a take started in the same millisecond.
+fn recording_path(directory: &Path, format: RecordingFormat) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let extension = format.extension();
+
+    let mut path = directory.join(format!("noisette-session-{timestamp}.{extension}"));
+    let mut take = 1;
+    while path.exists() {
+        take += 1;
+        path = directory.join(format!("noisette-session-{timestamp}-{take}.{extension}"));
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    /// Stereo source counting up from zero, forever.
+    struct Counter(Arc<AtomicUsize>);
+
+    impl Iterator for Counter {
+        type Item = f32;
+
+        fn next(&mut self) -> Option<f32> {
+            Some(self.0.fetch_add(1, Ordering::SeqCst) as f32)
+        }
+    }
+
+    impl Source for Counter {
+        fn current_frame_len(&self) -> Option<usize> {
+            None
+        }
+
+        fn channels(&self) -> u16 {
+            2
+        }
+
+        fn sample_rate(&self) -> u32 {
+            44100
+        }
+
+        fn total_duration(&self) -> Option<Duration> {
+            None
+        }
+    }
+
+    #[test]
+    fn stop_writes_the_buffered_tail() {
+        let directory = std::env::temp_dir().join(format!("noisette-recorder-{}", std::process::id()));
+        let mut recorder = SessionRecorder::new(2, 44100);
+        let path = recorder.start(&RecordingTarget { directory: directory.clone(), format: RecordingFormat::Wav }).unwrap();
+
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicBool::new(false));
+        let output = {
+            let mut tap = RecordingTap::new(Counter(Arc::clone(&pulled)), recorder.slot());
+            let done = Arc::clone(&done);
+            thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    tap.by_ref().take(64).for_each(drop);
+                    thread::sleep(Duration::from_millis(1));
+                }
+            })
+        };
+
+        // Meno di un blocco intero: senza la coda il file resterebbe vuoto
+        while pulled.load(Ordering::SeqCst) < 1000 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        let before_stop = pulled.load(Ordering::SeqCst);
+        assert_eq!(recorder.stop(), Some(path.clone()));
+        done.store(true, Ordering::SeqCst);
+        output.join().unwrap();
+
+        let samples: Vec<f32> = hound::WavReader::open(&path)
+            .unwrap()
+            .into_samples()
+            .map(Result::unwrap)
+            .collect();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(samples.len() >= before_stop, "{} < {before_stop}", samples.len());
+        assert!(samples.len().is_multiple_of(2));
+        assert!(samples.iter().enumerate().all(|(idx, sample)| *sample == idx as f32));
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("noisette-{name}-{}", std::process::id()))
+    }
+
+    #[test]
+    fn busy_slot_keeps_the_chunk_for_the_next_flush() {
+        let directory = temp_dir("recorder-busy");
+        let mut recorder = SessionRecorder::new(2, 44100);
+        let path = recorder.start(&RecordingTarget { directory: directory.clone(), format: RecordingFormat::Wav }).unwrap();
+
+        let pulled = Arc::new(AtomicUsize::new(0));
+        let mut tap = RecordingTap::new(Counter(Arc::clone(&pulled)), recorder.slot());
+        let chunk_len = CHUNK_FRAMES * 2;
+        {
+            // Il thread audio trova il lock occupato per il primo blocco
+            let slot = recorder.slot();
+            let _busy = slot.sender.lock().unwrap();
+            tap.by_ref().take(chunk_len).for_each(drop);
+        }
+        tap.by_ref().take(chunk_len).for_each(drop);
+        drop(tap);
+
+        assert_eq!(recorder.stop(), Some(path.clone()));
+        let samples: Vec<f32> = hound::WavReader::open(&path)
+            .unwrap()
+            .into_samples()
+            .map(Result::unwrap)
+            .collect();
+        let _ = fs::remove_dir_all(&directory);
+
+        // Il primo blocco non va perso: i campioni partono da zero senza buchi
+        assert!(samples.len() > chunk_len, "{} <= {chunk_len}", samples.len());
+        assert!(samples.iter().enumerate().all(|(idx, sample)| *sample == idx as f32));
+    }
+
+    #[test]
+    fn dropping_the_recorder_finalizes_the_file() {
+        let directory = temp_dir("recorder-drop");
+        let mut recorder = SessionRecorder::new(2, 44100);
+        let path = recorder.start(&RecordingTarget { directory: directory.clone(), format: RecordingFormat::Wav }).unwrap();
+
+        let mut tap = RecordingTap::new(Counter(Arc::new(AtomicUsize::new(0))), recorder.slot());
+        tap.by_ref().take(CHUNK_FRAMES * 2).for_each(drop);
+        drop(tap);
+        drop(recorder);
+
+        let reader = hound::WavReader::open(&path).unwrap();
+        let _ = fs::remove_dir_all(&directory);
+        assert_eq!(reader.len() as usize, CHUNK_FRAMES * 2);
+    }
+
+    #[test]
+    fn takes_in_the_same_millisecond_get_their_own_file() {
+        let directory = temp_dir("recorder-names");
+        fs::create_dir_all(&directory).unwrap();
+
+        let paths: Vec<PathBuf> = (0..3)
+            .map(|_| {
+                let path = recording_path(&directory, RecordingFormat::Flac);
+                fs::write(&path, b"").unwrap();
+                path
+            })
+            .collect();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(paths.iter().all(|path| path.extension().is_some_and(|ext| ext == "flac")));
+        assert_ne!(paths[0], paths[1]);
+        assert_ne!(paths[1], paths[2]);
+        assert_ne!(paths[0], paths[2]);
+    }
+
+    #[test]
+    fn flac_sessions_decode_back_to_the_recorded_samples() {
+        let directory = temp_dir("recorder-flac");
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("session.flac");
+
+        // Piu' di un blocco, con una coda incompleta
+        let samples: Vec<f32> = (0..10_000)
+            .map(|idx| ((idx / 2) as f32 * 0.01).sin() * 0.5)
+            .collect();
+        let mut writer = SessionWriter::create(&path, RecordingFormat::Flac, 2, 44100).unwrap();
+        for chunk in samples.chunks(777) {
+            writer.write(chunk).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let decoder = rodio::Decoder::new(std::io::BufReader::new(File::open(&path).unwrap())).unwrap();
+        assert_eq!(decoder.channels(), 2);
+        assert_eq!(decoder.sample_rate(), 44100);
+        let decoded: Vec<f32> = decoder.convert_samples().collect();
+        let _ = fs::remove_dir_all(&directory);
+
+        assert_eq!(decoded.len(), samples.len());
+        assert!(decoded.iter().zip(&samples).all(|(decoded, sample)| (decoded - sample).abs() < 1e-4));
+    }
+}
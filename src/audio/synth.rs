@@ -0,0 +1,332 @@
+use serde::{Deserialize, Serialize};
+use std::f32::consts::TAU;
+use std::fmt;
+
+pub const SYNTH_SAMPLE_RATE: u32 = 44_100;
+
+const AMPLITUDE: f32 = 0.5;
+const FADE_MS: u32 = 5;
+const MAX_DURATION_MS: u32 = 60_000;
+
+const DTMF_TONE_MS: u32 = 150;
+const DTMF_GAP_MS: u32 = 100;
+
+const COUNTDOWN_BEEP_MS: u32 = 150;
+const COUNTDOWN_FINAL_MS: u32 = 600;
+const COUNTDOWN_FREQUENCY: f32 = 880.0;
+const COUNTDOWN_FINAL_FREQUENCY: f32 = 1320.0;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NoiseColor {
+    White,
+    Pink,
+}
+
+/// A sound that is synthesized on the fly instead of being read from a file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Generator {
+    Tone {
+        waveform: Waveform,
+        frequency: f32,
+        duration_ms: u32,
+    },
+    Noise {
+        color: NoiseColor,
+        duration_ms: u32,
+    },
+    Dtmf {
+        digits: String,
+    },
+    /// `beeps` short beeps spaced by `interval_ms`, followed by a long one.
+    Countdown {
+        beeps: u32,
+        interval_ms: u32,
+    },
+}
+
+impl Generator {
+    pub fn default_tone() -> Self {
+        Generator::Tone { waveform: Waveform::Sine, frequency: 1000.0, duration_ms: 1000 }
+    }
+
+    pub fn default_noise() -> Self {
+        Generator::Noise { color: NoiseColor::White, duration_ms: 1000 }
+    }
+
+    pub fn default_dtmf() -> Self {
+        Generator::Dtmf { digits: "123".to_string() }
+    }
+
+    pub fn default_countdown() -> Self {
+        Generator::Countdown { beeps: 3, interval_ms: 1000 }
+    }
+
+    /// Renders the whole sound as mono samples at [`SYNTH_SAMPLE_RATE`],
+    /// at most [`MAX_DURATION_MS`] long.
+    pub fn render(&self) -> Vec<f32> {
+        let max_len = sample_count(MAX_DURATION_MS);
+        let mut samples = Vec::new();
+
+        match self {
+            Generator::Tone { waveform, frequency, duration_ms } => {
+                push_tone(&mut samples, *waveform, &[*frequency], *duration_ms);
+            }
+            Generator::Noise { color, duration_ms } => {
+                push_noise(&mut samples, *color, *duration_ms);
+            }
+            Generator::Dtmf { digits } => {
+                for (low, high) in digits.chars().filter_map(dtmf_frequencies) {
+                    if samples.len() >= max_len {
+                        break;
+                    }
+                    push_tone(&mut samples, Waveform::Sine, &[low, high], DTMF_TONE_MS);
+                    push_silence(&mut samples, DTMF_GAP_MS);
+                }
+            }
+            Generator::Countdown { beeps, interval_ms } => {
+                for _ in 0..*beeps {
+                    // Troppi bip: il resto verrebbe comunque tagliato
+                    if samples.len() >= max_len {
+                        break;
+                    }
+                    push_tone(&mut samples, Waveform::Sine, &[COUNTDOWN_FREQUENCY], COUNTDOWN_BEEP_MS);
+                    push_silence(&mut samples, interval_ms.saturating_sub(COUNTDOWN_BEEP_MS));
+                }
+                push_tone(&mut samples, Waveform::Sine, &[COUNTDOWN_FINAL_FREQUENCY], COUNTDOWN_FINAL_MS);
+            }
+        }
+
+        samples.truncate(max_len);
+        samples
+    }
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Generator::Tone { waveform, frequency, duration_ms } => {
+                write!(f, "{waveform:?} {frequency} Hz, {duration_ms} ms")
+            }
+            Generator::Noise { color, duration_ms } => write!(f, "{color:?} noise, {duration_ms} ms"),
+            Generator::Dtmf { digits } => write!(f, "DTMF {digits}"),
+            Generator::Countdown { beeps, interval_ms } => {
+                write!(f, "Countdown {beeps} x {interval_ms} ms")
+            }
+        }
+    }
+}
+
+fn sample_count(duration_ms: u32) -> usize {
+    (SYNTH_SAMPLE_RATE as u64 * duration_ms.min(MAX_DURATION_MS) as u64 / 1000) as usize
+}
+
+/// Linear fade in/out envelope, avoids clicks at the edges of each tone.
+fn envelope(index: usize, len: usize) -> f32 {
+    let fade = sample_count(FADE_MS).max(1);
+    let from_edge = index.min(len.saturating_sub(index + 1));
+    (from_edge as f32 / fade as f32).min(1.0)
+}
+
+fn push_tone(samples: &mut Vec<f32>, waveform: Waveform, frequencies: &[f32], duration_ms: u32) {
+    let len = sample_count(duration_ms);
+    let gain = AMPLITUDE / frequencies.len().max(1) as f32;
+
+    for i in 0..len {
+        let t = i as f32 / SYNTH_SAMPLE_RATE as f32;
+        let value: f32 = frequencies
+            .iter()
+            .map(|frequency| {
+                let phase = (TAU * frequency * t).sin();
+                match waveform {
+                    Waveform::Sine => phase,
+                    Waveform::Square => phase.signum(),
+                }
+            })
+            .sum();
+
+        samples.push(value * gain * envelope(i, len));
+    }
+}
+
+fn push_noise(samples: &mut Vec<f32>, color: NoiseColor, duration_ms: u32) {
+    let len = sample_count(duration_ms);
+    let mut rng = XorShift(0x2545_F491);
+    // Filtro di Paul Kellet per il rumore rosa
+    let mut b = [0.0f32; 7];
+
+    for i in 0..len {
+        let white = rng.next_f32();
+        let value = match color {
+            NoiseColor::White => white,
+            NoiseColor::Pink => {
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.153852;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b[..6].iter().sum::<f32>() + b[6] + white * 0.5362;
+                b[6] = white * 0.115926;
+                pink * 0.11
+            }
+        };
+
+        samples.push(value * AMPLITUDE * envelope(i, len));
+    }
+}
+
+fn push_silence(samples: &mut Vec<f32>, duration_ms: u32) {
+    samples.extend(std::iter::repeat_n(0.0, sample_count(duration_ms)));
+}
+
+fn dtmf_frequencies(digit: char) -> Option<(f32, f32)> {
+    let (row, column) = match digit.to_ascii_uppercase() {
+        '1' => (0, 0), '2' => (0, 1), '3' => (0, 2), 'A' => (0, 3),
+        '4' => (1, 0), '5' => (1, 1), '6' => (1, 2), 'B' => (1, 3),
+        '7' => (2, 0), '8' => (2, 1), '9' => (2, 2), 'C' => (2, 3),
+        '*' => (3, 0), '0' => (3, 1), '#' => (3, 2), 'D' => (3, 3),
+        _ => return None,
+    };
+
+    const LOW: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+    const HIGH: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+
+    Some((LOW[row], HIGH[column]))
+}
+
+/// Small deterministic generator, enough for noise without pulling in `rand`.
+struct XorShift(u32);
+
+impl XorShift {
+    fn next_f32(&mut self) -> f32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        (x as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(duration_ms: u32) -> usize {
+        SYNTH_SAMPLE_RATE as usize * duration_ms as usize / 1000
+    }
+
+    /// Strength of `frequency` in `samples`, with the Goertzel algorithm.
+    fn magnitude(samples: &[f32], frequency: f32) -> f32 {
+        let coefficient = 2.0 * (TAU * frequency / SYNTH_SAMPLE_RATE as f32).cos();
+        let (mut previous, mut before) = (0.0f32, 0.0f32);
+        for sample in samples {
+            let current = sample + coefficient * previous - before;
+            before = previous;
+            previous = current;
+        }
+        (previous * previous + before * before - coefficient * previous * before).sqrt() / samples.len() as f32
+    }
+
+    fn peak(samples: &[f32]) -> f32 {
+        samples.iter().fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn tones_last_their_duration_and_fade_at_the_edges() {
+        let samples = Generator::Tone { waveform: Waveform::Sine, frequency: 440.0, duration_ms: 250 }.render();
+
+        assert_eq!(samples.len(), ms(250));
+        assert!(peak(&samples) <= AMPLITUDE);
+        assert!(peak(&samples) > AMPLITUDE * 0.99);
+        assert_eq!(samples[0], 0.0);
+        assert_eq!(samples[samples.len() - 1], 0.0);
+        assert!(magnitude(&samples, 440.0) > 10.0 * magnitude(&samples, 880.0));
+    }
+
+    #[test]
+    fn square_tones_sit_at_full_amplitude() {
+        let samples = Generator::Tone { waveform: Waveform::Square, frequency: 100.0, duration_ms: 100 }.render();
+
+        // Fuori dalle dissolvenze solo i due livelli, piu' gli zeri della sinusoide
+        let fade = ms(FADE_MS);
+        assert!(samples[fade..samples.len() - fade]
+            .iter()
+            .all(|sample| sample.abs() == AMPLITUDE || *sample == 0.0));
+    }
+
+    #[test]
+    fn noise_is_bounded_and_repeatable() {
+        for color in [NoiseColor::White, NoiseColor::Pink] {
+            let noise = Generator::Noise { color, duration_ms: 500 };
+            let samples = noise.render();
+
+            assert_eq!(samples.len(), ms(500));
+            assert!(peak(&samples) <= AMPLITUDE, "{color:?} peak {}", peak(&samples));
+            assert!(peak(&samples) > AMPLITUDE * 0.1, "{color:?} peak {}", peak(&samples));
+            assert_eq!(samples, noise.render());
+        }
+    }
+
+    #[test]
+    fn dtmf_digits_mix_their_row_and_column() {
+        assert_eq!(dtmf_frequencies('1'), Some((697.0, 1209.0)));
+        assert_eq!(dtmf_frequencies('5'), Some((770.0, 1336.0)));
+        assert_eq!(dtmf_frequencies('#'), Some((941.0, 1477.0)));
+        assert_eq!(dtmf_frequencies('d'), Some((941.0, 1633.0)));
+        assert_eq!(dtmf_frequencies('x'), None);
+
+        // I caratteri sconosciuti vengono saltati
+        let samples = Generator::Dtmf { digits: "1x#".to_string() }.render();
+        let digit = ms(DTMF_TONE_MS) + ms(DTMF_GAP_MS);
+        assert_eq!(samples.len(), 2 * digit);
+        assert!(peak(&samples) <= AMPLITUDE);
+
+        let (one, pound) = (&samples[..ms(DTMF_TONE_MS)], &samples[digit..digit + ms(DTMF_TONE_MS)]);
+        assert!(magnitude(one, 697.0) > 10.0 * magnitude(one, 941.0));
+        assert!(magnitude(one, 1209.0) > 10.0 * magnitude(one, 1477.0));
+        assert!(magnitude(pound, 941.0) > 10.0 * magnitude(pound, 697.0));
+        assert!(magnitude(pound, 1477.0) > 10.0 * magnitude(pound, 1209.0));
+        assert!(samples[ms(DTMF_TONE_MS)..digit].iter().all(|sample| *sample == 0.0));
+    }
+
+    #[test]
+    fn countdowns_end_on_a_long_high_beep() {
+        let samples = Generator::Countdown { beeps: 3, interval_ms: 400 }.render();
+        assert_eq!(samples.len(), 3 * ms(400) + ms(COUNTDOWN_FINAL_MS));
+
+        let beep = &samples[..ms(COUNTDOWN_BEEP_MS)];
+        let gap = &samples[ms(COUNTDOWN_BEEP_MS)..ms(400)];
+        let last = &samples[3 * ms(400)..];
+        assert!(magnitude(beep, COUNTDOWN_FREQUENCY) > 10.0 * magnitude(beep, COUNTDOWN_FINAL_FREQUENCY));
+        assert!(gap.iter().all(|sample| *sample == 0.0));
+        assert!(magnitude(last, COUNTDOWN_FINAL_FREQUENCY) > 10.0 * magnitude(last, COUNTDOWN_FREQUENCY));
+
+        // Un intervallo piu' corto del bip non lascia pause
+        let packed = Generator::Countdown { beeps: 2, interval_ms: 50 }.render();
+        assert_eq!(packed.len(), 2 * ms(COUNTDOWN_BEEP_MS) + ms(COUNTDOWN_FINAL_MS));
+    }
+
+    #[test]
+    fn durations_are_capped() {
+        let tone = Generator::Tone { waveform: Waveform::Sine, frequency: 440.0, duration_ms: u32::MAX }.render();
+        assert_eq!(tone.len(), ms(MAX_DURATION_MS));
+
+        let noise = Generator::Noise { color: NoiseColor::White, duration_ms: 90_000 }.render();
+        assert_eq!(noise.len(), ms(MAX_DURATION_MS));
+
+        // Anche la somma dei passi non supera il limite
+        let countdown = Generator::Countdown { beeps: 1, interval_ms: u32::MAX }.render();
+        assert_eq!(countdown.len(), ms(MAX_DURATION_MS));
+        let countdown = Generator::Countdown { beeps: u32::MAX, interval_ms: 1000 }.render();
+        assert_eq!(countdown.len(), ms(MAX_DURATION_MS));
+        let dtmf = Generator::Dtmf { digits: "0".repeat(1000) }.render();
+        assert_eq!(dtmf.len(), ms(MAX_DURATION_MS));
+    }
+}
@@ -3,57 +3,136 @@ use web_sys::{window, AudioContext, HtmlMediaElement, AudioBufferSourceNode};
 
 use crate::Sound;
 
-use super::interface::AudioBackend;
+use super::interface::{AudioBackend, AudioEvent, MasterVolume, PlaybackState};
+use super::synth::SYNTH_SAMPLE_RATE;
 
 pub struct WebAudio {
     context: AudioContext,
-    audio_element: Option<HtmlMediaElement>
+    audio_element: Option<(String, HtmlMediaElement)>,
+    master: MasterVolume,
 }
 
 impl WebAudio {
-    pub fn new(device: Option<u8>) -> Self {
+    pub fn new() -> Self {
         Self {
             context: AudioContext::new().unwrap(),
-            audio_element: None
+            audio_element: None,
+            master: MasterVolume::default(),
         }
     }
 
+    pub fn set_repaint_context(&mut self, _ctx: egui::Context) {
+        // Lo stato viene letto direttamente dall'elemento audio
+    }
+
+    pub fn poll_events(&self) -> Vec<AudioEvent> {
+        Vec::new()
+    }
+
+    pub fn state(&self) -> PlaybackState {
+        let mut state = PlaybackState {
+            master: self.master,
+            ..PlaybackState::default()
+        };
+        if let Some((key, audio)) = &self.audio_element && !audio.paused() {
+            state.playing.insert(key.clone());
+        }
+        state
+    }
+
     pub fn set_device(&mut self, _device: Option<String>) {
         // Stub: non supportato su web
         web_sys::console::log_1(&"set_device not supported on Web".into());
     }
+
+    pub fn set_backend(&mut self, _kind: super::AudioBackendKind) {
+        // Stub: su web c'e' solo il browser
+    }
+
+    pub fn start_recording(&mut self, _target: super::RecordingTarget) {
+        // Stub: non supportato su web
+        web_sys::console::log_1(&"Recording not supported on Web".into());
+    }
+
+    pub fn stop_recording(&mut self) {
+    }
+
+    pub fn is_recording(&self) -> bool {
+        false
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.master.set_volume(volume);
+        self.apply_master();
+    }
+
+    pub fn nudge_volume(&mut self, step: f32) {
+        self.set_volume(self.master.volume + step);
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.master.muted = !self.master.muted;
+        self.apply_master();
+    }
+
+    /// Only the file being played can follow the master volume: generated
+    /// sounds pick it up when they start.
+    fn apply_master(&self) {
+        if let Some((_, audio)) = &self.audio_element {
+            audio.set_volume(f64::from(self.master.gain()));
+        }
+    }
 }
 
 impl AudioBackend for WebAudio {
-    fn play(&mut self, sound: &Sound) {
+    fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String> {
         web_sys::console::log_1(&"Playing audio in Web!".into());
-        if let Some(path) = &sound.path {
+        let gain = gain * self.master.gain();
+        if let Some(generator) = &sound.generator {
+            let samples = generator.render();
+            let buffer = self.context
+                .create_buffer(1, samples.len() as u32, SYNTH_SAMPLE_RATE as f32).unwrap();
+            buffer.copy_to_channel(&samples, 0).unwrap();
+
+            let source: AudioBufferSourceNode = self.context.create_buffer_source().unwrap();
+            source.set_buffer(Some(&buffer));
+
+            let gain_node = self.context.create_gain().unwrap();
+            gain_node.gain().set_value(gain);
+            source.connect_with_audio_node(&gain_node).unwrap();
+            gain_node.connect_with_audio_node(&self.context.destination()).unwrap();
+            let _ = source.start();
+        } else if let Some(path) = &sound.path {
             let document = window().unwrap().document().unwrap();
             let audio = document
                 .create_element("audio").unwrap()
                 .dyn_into::<HtmlMediaElement>().unwrap();
 
             audio.set_src(path);
+            audio.set_volume(f64::from(gain.clamp(0.0, 1.0)));
             audio.set_autoplay(true);
 
             let _ = audio.play(); // Starts playback
-            self.audio_element = Some(audio);
+            self.audio_element = Some((path.clone(), audio));
         }
+
+        Ok(())
+    }
+
+    fn stop(&mut self, _sound: &Sound) {
+        self.stop_all();
     }
 
-    fn stop(&mut self) {
+    fn stop_all(&mut self) {
         web_sys::console::log_1(&"Stopping audio".into());
-        if let Some(audio) = &self.audio_element {
+        if let Some((_, audio)) = &self.audio_element {
             let _ = audio.pause();
             audio.set_current_time(0.0);
         }
-
     }
 
-    fn is_playing(&self) -> bool {
-        self.audio_element
-            .as_ref()
-            .map_or(false, |a| !a.paused())
+    fn is_playing(&self, sound: Option<&Sound>) -> bool {
+        self.state().is_playing(sound)
     }
 }
 
@@ -67,4 +146,8 @@ pub fn get_default_output_device() -> Option<String> {
 
 pub fn get_device_from_name(name: Option<String>) -> Option<String> {
     None
+}
+
+pub fn default_recordings_dir() -> std::path::PathBuf {
+    std::path::PathBuf::new()
 }
\ No newline at end of file
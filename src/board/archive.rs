@@ -0,0 +1,314 @@
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::Sound;
+use crate::board::Board;
+
+/// Extension of the exported boards.
+pub const EXTENSION: &str = "noisette";
+
+const MANIFEST: &str = "board.json";
+const SOUNDS_DIR: &str = "sounds/";
+const VERSION: u32 = 1;
+
+/// What `board.json` holds: the board with its shortcuts and settings, the
+/// sound paths pointing at the files in the archive.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Manifest {
+    version: u32,
+    board: Board,
+}
+
+/// Where the sounds of imported boards are unpacked by default.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn default_library_dir() -> PathBuf {
+    std::env::current_dir()
+        .unwrap_or_default()
+        .join("library")
+}
+
+/// Packs `board` and the audio files of `sounds` into a zip archive.
+pub fn export(board: &Board, sounds: &[Sound]) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    // I file audio sono gia' compressi
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let mut manifest = Manifest {
+        version: VERSION,
+        board: Board {
+            sounds: Vec::with_capacity(sounds.len()),
+            ..board.clone()
+        },
+    };
+
+    for (idx, sound) in sounds.iter().enumerate() {
+        let mut packed = Sound {
+            editing: false,
+            data: None,
+            ..sound.clone()
+        };
+
+        if let Some(path) = &sound.path {
+            let bytes = match &sound.data {
+                Some(data) => data.clone(),
+                None => std::fs::read(path).map_err(|error| format!("Cannot read {path}: {error}"))?,
+            };
+
+            let entry = format!("{SOUNDS_DIR}{idx}-{}", file_name(path));
+            zip.start_file(entry.as_str(), stored).map_err(zip_error)?;
+            zip.write_all(&bytes).map_err(|error| error.to_string())?;
+            packed.path = Some(entry);
+        }
+
+        manifest.board.sounds.push(packed);
+    }
+
+    let json = serde_json::to_vec_pretty(&manifest).map_err(|error| error.to_string())?;
+    zip.start_file(MANIFEST, FileOptions::default()).map_err(zip_error)?;
+    zip.write_all(&json).map_err(|error| error.to_string())?;
+
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+/// Reads an archive written by [`export`].
+///
+/// The audio files are unpacked into a new folder of `library`, named after
+/// the board, and the sounds point at them. Without a library, as in the
+/// browser, the sounds keep the files in memory.
+pub fn import(bytes: &[u8], library: Option<&Path>) -> Result<Board, String> {
+    let mut zip = ZipArchive::new(Cursor::new(bytes)).map_err(zip_error)?;
+
+    let manifest: Manifest = {
+        let file = zip.by_name(MANIFEST).map_err(|_| format!("Not a board archive: {MANIFEST} is missing"))?;
+        serde_json::from_reader(file).map_err(|error| format!("Invalid {MANIFEST}: {error}"))?
+    };
+
+    if manifest.version > VERSION {
+        return Err(format!("The board was exported by a newer version (format {})", manifest.version));
+    }
+
+    let mut board = manifest.board;
+    let directory = match library {
+        Some(library) => Some(unique_dir(library, &board.name)?),
+        None => None,
+    };
+
+    // Una board importata a meta' non deve restare nella libreria
+    if let Err(error) = unpack_sounds(&mut zip, &mut board, directory.as_deref()) {
+        if let Some(directory) = &directory {
+            let _ = std::fs::remove_dir_all(directory);
+        }
+        return Err(error);
+    }
+
+    Ok(board)
+}
+
+/// Points every sound of `board` at its file, unpacked into `directory` or
+/// kept in memory.
+fn unpack_sounds(zip: &mut ZipArchive<Cursor<&[u8]>>, board: &mut Board, directory: Option<&Path>) -> Result<(), String> {
+    for sound in &mut board.sounds {
+        // I byte arrivano solo dall'archivio, mai dal manifest
+        sound.data = None;
+        let Some(entry) = sound.path.take() else {
+            continue;
+        };
+        // Un percorso fuori dall'archivio puntava al disco di chi l'ha esportata
+        if !entry.starts_with(SOUNDS_DIR) {
+            continue;
+        }
+
+        let mut bytes = Vec::new();
+        zip.by_name(&entry)
+            .map_err(|_| format!("{entry} is missing from the archive"))?
+            .read_to_end(&mut bytes)
+            .map_err(|error| error.to_string())?;
+
+        // Solo il nome del file: un percorso nell'archivio non deve uscire dalla libreria
+        let name = file_name(&entry);
+
+        match directory {
+            Some(directory) => {
+                let path = directory.join(name);
+                std::fs::write(&path, &bytes)
+                    .map_err(|error| format!("Cannot write {}: {error}", path.display()))?;
+                sound.path = Some(path.display().to_string());
+            }
+            None => {
+                sound.path = Some(name.to_string());
+                sound.data = Some(bytes);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("sound")
+}
+
+/// Creates `library/<name>`, or `<name> 2` and so on if it already exists.
+fn unique_dir(library: &Path, name: &str) -> Result<PathBuf, String> {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_') { c } else { '_' })
+        .collect();
+    let name = if name.trim().is_empty() { "board" } else { name.trim() };
+
+    let mut directory = library.join(name);
+    let mut copy = 1;
+    while directory.exists() {
+        copy += 1;
+        directory = library.join(format!("{name} {copy}"));
+    }
+
+    std::fs::create_dir_all(&directory)
+        .map_err(|error| format!("Cannot create {}: {error}", directory.display()))?;
+    Ok(directory)
+}
+
+fn zip_error(error: zip::result::ZipError) -> String {
+    format!("Board archive: {error}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audio::synth::Generator;
+    use crate::shortcut::keycodes::SerializableKeycode;
+
+    use super::*;
+
+    #[test]
+    fn boards_round_trip_in_memory() {
+        let clip = Sound {
+            name: Some("Applause".to_string()),
+            path: Some("clips/applause.wav".to_string()),
+            data: Some(vec![1, 2, 3, 4]),
+            shortcut: Some(vec![SerializableKeycode::LControl, SerializableKeycode::Key1]),
+            editing: false,
+            ..Sound::default()
+        };
+        let tone = Sound {
+            generator: Some(Generator::default_tone()),
+            editing: false,
+            ..Sound::default()
+        };
+        let board = Board {
+            stop_on_new: Some(false),
+            ..Board::new("Live")
+        };
+
+        let bytes = export(&board, &[clip.clone(), tone.clone()]).unwrap();
+        let imported = import(&bytes, None).unwrap();
+
+        // I suoni importati sono nuovi: non condividono la riproduzione con gli originali
+        let ids: Vec<_> = imported.sounds.iter().map(|sound| sound.id).collect();
+        assert!(ids[0] != clip.id && ids[1] != tone.id);
+
+        // Senza libreria i file restano in memoria, con il nome che avevano nell'archivio
+        let expected = Board {
+            sounds: vec![
+                Sound {
+                    path: Some("0-applause.wav".to_string()),
+                    id: ids[0],
+                    ..clip
+                },
+                Sound { id: ids[1], ..tone },
+            ],
+            ..board
+        };
+        assert_eq!(imported, expected);
+    }
+
+    /// An archive with `board` as its manifest and the given entries, as a
+    /// hand-edited or malicious file could be.
+    fn archive(board: &Board, entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, bytes) in entries {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(bytes).unwrap();
+        }
+
+        let manifest = Manifest { version: VERSION, board: board.clone() };
+        zip.start_file(MANIFEST, FileOptions::default()).unwrap();
+        zip.write_all(&serde_json::to_vec(&manifest).unwrap()).unwrap();
+        zip.finish().unwrap().into_inner()
+    }
+
+    fn sound_at(path: &str) -> Sound {
+        Sound {
+            path: Some(path.to_string()),
+            editing: false,
+            ..Sound::default()
+        }
+    }
+
+    fn temp_library(name: &str) -> PathBuf {
+        let library = std::env::temp_dir().join(format!("noisette-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&library);
+        library
+    }
+
+    #[test]
+    fn import_unpacks_into_the_library() {
+        let library = temp_library("library-import");
+        let board = Board {
+            sounds: vec![
+                sound_at("sounds/0-kick.wav"),
+                sound_at("/home/someone/secret.wav"),
+                sound_at("../outside.wav"),
+                Sound {
+                    data: Some(vec![9, 9]),
+                    ..sound_at("sounds/../../escape.wav")
+                },
+            ],
+            ..Board::new("Live: set/1")
+        };
+        let bytes = archive(&board, &[("sounds/0-kick.wav", b"kick"), ("sounds/../../escape.wav", b"escape")]);
+
+        let imported = import(&bytes, Some(&library)).unwrap();
+        let directory = library.join("Live_ set_1");
+        let paths: Vec<Option<String>> = imported.sounds.iter().map(|sound| sound.path.clone()).collect();
+        let kick = std::fs::read(directory.join("0-kick.wav")).unwrap();
+        let escape = std::fs::read(directory.join("escape.wav")).unwrap();
+        let _ = std::fs::remove_dir_all(&library);
+
+        assert_eq!(
+            paths,
+            vec![
+                Some(directory.join("0-kick.wav").display().to_string()),
+                None,
+                None,
+                Some(directory.join("escape.wav").display().to_string()),
+            ]
+        );
+        assert_eq!(kick, b"kick");
+        // Il nome e' ridotto al file: nulla esce dalla cartella della board
+        assert_eq!(escape, b"escape");
+        assert!(imported.sounds.iter().all(|sound| sound.data.is_none()));
+    }
+
+    #[test]
+    fn failed_imports_leave_no_board_behind() {
+        let library = temp_library("library-failed");
+        let board = Board {
+            sounds: vec![sound_at("sounds/0-kick.wav"), sound_at("sounds/1-missing.wav")],
+            ..Board::new("Broken")
+        };
+        let bytes = archive(&board, &[("sounds/0-kick.wav", b"kick")]);
+
+        let error = import(&bytes, Some(&library)).unwrap_err();
+        let leftovers = std::fs::read_dir(&library).map(|entries| entries.count()).unwrap_or_default();
+        let _ = std::fs::remove_dir_all(&library);
+
+        assert!(error.contains("sounds/1-missing.wav"), "{error}");
+        assert_eq!(leftovers, 0);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::Sound;
+
+pub mod archive;
+
+/// A named page of sounds, so the same shortcuts can play different sounds
+/// on each board.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Board {
+    pub name: String,
+    /// Empty while the board is active: its sounds are in `Noisette::sounds`.
+    pub sounds: Vec<Sound>,
+    /// Overrides the "Interrupt on new" setting on this board.
+    pub stop_on_new: Option<bool>,
+}
+
+impl Board {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Every board and which one is shown and triggered.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Boards {
+    list: Vec<Board>,
+    active: usize,
+}
+
+impl Default for Boards {
+    fn default() -> Self {
+        Self {
+            list: vec![Board::new("Default")],
+            active: 0,
+        }
+    }
+}
+
+impl Boards {
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    pub fn active_board(&self) -> &Board {
+        &self.list[self.active]
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Board> {
+        self.list.get(idx)
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut Board> {
+        self.list.get_mut(idx)
+    }
+
+    /// Fixes the state loaded from an older version, which had no boards.
+    pub fn repair(&mut self) {
+        if self.list.is_empty() {
+            self.list.push(Board::new("Default"));
+        }
+        self.active = self.active.min(self.list.len() - 1);
+    }
+
+    /// Shows board `target`: the sounds on screen go back to the board they
+    /// belong to and `sounds` gets the ones of `target`.
+    pub fn switch(&mut self, sounds: &mut Vec<Sound>, target: usize) {
+        if target == self.active || target >= self.list.len() {
+            return;
+        }
+
+        self.list[self.active].sounds = std::mem::take(sounds);
+        *sounds = std::mem::take(&mut self.list[target].sounds);
+        self.active = target;
+    }
+
+    /// Adds an empty board and shows it.
+    pub fn add(&mut self, sounds: &mut Vec<Sound>) {
+        let name = format!("Board {}", self.list.len() + 1);
+        self.insert(sounds, Board::new(name));
+    }
+
+    /// Adds `board`, e.g. an imported one, and shows it.
+    pub fn insert(&mut self, sounds: &mut Vec<Sound>, board: Board) {
+        self.list.push(board);
+        self.switch(sounds, self.list.len() - 1);
+    }
+
+    /// The sounds of board `idx`; `sounds` are the ones of the active board.
+    pub fn sounds_of<'a>(&'a self, idx: usize, sounds: &'a [Sound]) -> &'a [Sound] {
+        if idx == self.active {
+            sounds
+        } else {
+            self.list.get(idx).map(|board| board.sounds.as_slice()).unwrap_or_default()
+        }
+    }
+
+    /// Removes board `idx` with its sounds. The last board cannot be removed.
+    pub fn remove(&mut self, sounds: &mut Vec<Sound>, idx: usize) {
+        if self.list.len() < 2 || idx >= self.list.len() {
+            return;
+        }
+
+        if idx == self.active {
+            let neighbour = if idx == 0 { 1 } else { idx - 1 };
+            self.switch(sounds, neighbour);
+        }
+
+        self.list.remove(idx);
+        if self.active > idx {
+            self.active -= 1;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Board> {
+        self.list.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Board> {
+        self.list.iter_mut()
+    }
+}
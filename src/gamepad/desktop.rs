@@ -0,0 +1,222 @@
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use gilrs::{Axis, Button, EventType, Gilrs};
+
+use super::{AxisEdges, GamepadAxis, GamepadButton, GamepadMonitor, GamepadSource, GamepadTrigger};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Every gamepad gilrs can see.
+pub struct GilrsSource {
+    gilrs: Gilrs,
+    axes: AxisEdges,
+}
+
+impl GilrsSource {
+    pub fn new() -> Result<Self, String> {
+        let gilrs = Gilrs::new().map_err(|e| format!("Gamepads unavailable: {e}"))?;
+
+        Ok(Self {
+            gilrs,
+            axes: AxisEdges::default(),
+        })
+    }
+}
+
+impl GamepadSource for GilrsSource {
+    fn next_trigger(&mut self) -> Option<GamepadTrigger> {
+        while let Some(event) = self.gilrs.next_event() {
+            let trigger = match event.event {
+                EventType::ButtonPressed(button, _) => button_from(button).map(GamepadTrigger::Button),
+                EventType::AxisChanged(axis, value, _) => {
+                    axis_from(axis).and_then(|axis| self.axes.update(axis, value))
+                }
+                _ => None,
+            };
+
+            if trigger.is_some() {
+                return trigger;
+            }
+        }
+
+        None
+    }
+}
+
+fn button_from(button: Button) -> Option<GamepadButton> {
+    Some(match button {
+        Button::South => GamepadButton::South,
+        Button::East => GamepadButton::East,
+        Button::North => GamepadButton::North,
+        Button::West => GamepadButton::West,
+        Button::C => GamepadButton::C,
+        Button::Z => GamepadButton::Z,
+        Button::LeftTrigger => GamepadButton::LeftTrigger,
+        Button::LeftTrigger2 => GamepadButton::LeftTrigger2,
+        Button::RightTrigger => GamepadButton::RightTrigger,
+        Button::RightTrigger2 => GamepadButton::RightTrigger2,
+        Button::Select => GamepadButton::Select,
+        Button::Start => GamepadButton::Start,
+        Button::Mode => GamepadButton::Mode,
+        Button::LeftThumb => GamepadButton::LeftThumb,
+        Button::RightThumb => GamepadButton::RightThumb,
+        Button::DPadUp => GamepadButton::DPadUp,
+        Button::DPadDown => GamepadButton::DPadDown,
+        Button::DPadLeft => GamepadButton::DPadLeft,
+        Button::DPadRight => GamepadButton::DPadRight,
+        Button::Unknown => return None,
+    })
+}
+
+fn axis_from(axis: Axis) -> Option<GamepadAxis> {
+    Some(match axis {
+        Axis::LeftStickX => GamepadAxis::LeftStickX,
+        Axis::LeftStickY => GamepadAxis::LeftStickY,
+        Axis::LeftZ => GamepadAxis::LeftZ,
+        Axis::RightStickX => GamepadAxis::RightStickX,
+        Axis::RightStickY => GamepadAxis::RightStickY,
+        Axis::RightZ => GamepadAxis::RightZ,
+        Axis::DPadX => GamepadAxis::DPadX,
+        Axis::DPadY => GamepadAxis::DPadY,
+        Axis::Unknown => return None,
+    })
+}
+
+/// Polls a [`GamepadSource`] on its own thread. Triggers go to the trigger
+/// loop through the channel returned by [`DesktopGamepadInput::take_events`].
+#[derive(Default)]
+pub struct DesktopGamepadInput {
+    events: Option<Receiver<GamepadTrigger>>,
+    monitor: GamepadMonitor,
+    repaint: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl DesktopGamepadInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts listening to the real gamepads.
+    pub fn start(&mut self) {
+        self.start_with(|| GilrsSource::new().map_err(|error| eprintln!("{error}")).ok());
+    }
+
+    /// Starts listening to the source built by `source`, which is created on
+    /// the polling thread.
+    pub fn start_with<S: GamepadSource>(&mut self, source: impl FnOnce() -> Option<S> + Send + 'static) {
+        let (sender, events) = mpsc::channel();
+        let monitor = self.monitor.clone();
+        let repaint = Arc::clone(&self.repaint);
+        self.events = Some(events);
+
+        thread::spawn(move || {
+            let Some(mut source) = source() else {
+                return;
+            };
+
+            loop {
+                while let Some(trigger) = source.next_trigger() {
+                    monitor.record(trigger);
+                    if sender.send(trigger).is_err() {
+                        return;
+                    }
+
+                    // La modalita' "learn" deve vedere subito l'input
+                    if let Ok(repaint) = repaint.lock() && let Some(ctx) = repaint.as_ref() {
+                        ctx.request_repaint();
+                    }
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+
+    /// The receiving end of the trigger channel; only the first call gets it.
+    pub fn take_events(&mut self) -> Option<Receiver<GamepadTrigger>> {
+        self.events.take()
+    }
+
+    pub fn monitor(&self) -> GamepadMonitor {
+        self.monitor.clone()
+    }
+
+    pub fn set_repaint_context(&mut self, ctx: egui::Context) {
+        if let Ok(mut repaint) = self.repaint.lock() {
+            *repaint = Some(ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::Sound;
+    use crate::audio::null::NullAudio;
+    use crate::audio::synth::Generator;
+    use crate::gamepad::{ScriptedEvent, ScriptedGamepad};
+    use crate::shortcut::trigger::{fire_gamepad, BoardBindings, GamepadBinding, SharedSnapshot, TriggerAction, TriggerSnapshot};
+    use crate::sound::RetriggerPolicy;
+
+    use super::*;
+
+    fn binding(trigger: GamepadTrigger, generator: Generator) -> GamepadBinding {
+        GamepadBinding {
+            trigger,
+            action: TriggerAction::PlaySound(Arc::new(Sound {
+                generator: Some(generator),
+                retrigger: RetriggerPolicy::Restart,
+                editing: false,
+                ..Sound::default()
+            })),
+        }
+    }
+
+    #[test]
+    fn scripted_inputs_play_their_sounds() {
+        let push_right = GamepadTrigger::Axis { axis: GamepadAxis::LeftStickX, positive: true };
+        let south = binding(GamepadTrigger::Button(GamepadButton::South), Generator::default_tone());
+        let right = binding(push_right, Generator::default_noise());
+        let snapshot = TriggerSnapshot {
+            boards: vec![BoardBindings {
+                gamepad_bindings: vec![south.clone(), right.clone()],
+                ..BoardBindings::default()
+            }],
+            stop_on_new: true,
+            ..TriggerSnapshot::default()
+        };
+
+        let mut input = DesktopGamepadInput::new();
+        input.start_with(|| {
+            Some(ScriptedGamepad::new([
+                ScriptedEvent::Pressed(GamepadButton::South),
+                ScriptedEvent::Axis(GamepadAxis::LeftStickX, 0.8),
+                ScriptedEvent::Axis(GamepadAxis::LeftStickX, 0.9),
+                ScriptedEvent::Axis(GamepadAxis::LeftStickX, 0.0),
+                ScriptedEvent::Axis(GamepadAxis::LeftStickX, 0.7),
+                // Nessun suono sulla sinistra
+                ScriptedEvent::Axis(GamepadAxis::LeftStickX, -0.7),
+            ]))
+        });
+        let events = input.take_events().unwrap();
+
+        let shared = SharedSnapshot::default();
+        let mut audio = NullAudio::new();
+        for _ in 0..4 {
+            let trigger = events.recv_timeout(Duration::from_secs(2)).unwrap();
+            fire_gamepad(&shared, &snapshot, trigger, &mut audio);
+        }
+
+        let key = |binding: &GamepadBinding| match &binding.action {
+            TriggerAction::PlaySound(sound) => sound.key().unwrap(),
+            _ => unreachable!(),
+        };
+        let played: Vec<_> = audio.history().iter().map(|record| record.key.clone()).collect();
+        assert_eq!(played, [key(&south), key(&right), key(&right)]);
+        assert_eq!(input.monitor().take(), Some(GamepadTrigger::Axis { axis: GamepadAxis::LeftStickX, positive: false }));
+    }
+}
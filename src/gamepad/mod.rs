@@ -0,0 +1,179 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::shortcut::monitor::InputMonitor;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    South,
+    East,
+    North,
+    West,
+    C,
+    Z,
+    LeftTrigger,
+    LeftTrigger2,
+    RightTrigger,
+    RightTrigger2,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    LeftZ,
+    RightStickX,
+    RightStickY,
+    RightZ,
+    DPadX,
+    DPadY,
+}
+
+/// A gamepad input a sound can be bound to.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadTrigger {
+    Button(GamepadButton),
+    /// An axis pushed past [`AXIS_THRESHOLD`] in one direction.
+    Axis { axis: GamepadAxis, positive: bool },
+}
+
+impl std::fmt::Display for GamepadTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GamepadTrigger::Button(button) => write!(f, "Pad {:?}", button),
+            GamepadTrigger::Axis { axis, positive } => {
+                write!(f, "Pad {:?} {}", axis, if *positive { '+' } else { '-' })
+            }
+        }
+    }
+}
+
+/// How far an axis has to move before it counts as pressed.
+pub const AXIS_THRESHOLD: f32 = 0.5;
+
+/// Turns axis values into triggers, once each time an axis crosses
+/// [`AXIS_THRESHOLD`].
+#[derive(Default)]
+pub struct AxisEdges {
+    pushed: HashMap<GamepadAxis, bool>,
+}
+
+impl AxisEdges {
+    pub fn update(&mut self, axis: GamepadAxis, value: f32) -> Option<GamepadTrigger> {
+        let direction = if value >= AXIS_THRESHOLD {
+            true
+        } else if value <= -AXIS_THRESHOLD {
+            false
+        } else {
+            self.pushed.remove(&axis);
+            return None;
+        };
+
+        let previous = self.pushed.insert(axis, direction);
+        (previous != Some(direction)).then_some(GamepadTrigger::Axis { axis, positive: direction })
+    }
+}
+
+/// Where gamepad triggers come from.
+pub trait GamepadSource {
+    /// The next trigger since the last call, without blocking.
+    fn next_trigger(&mut self) -> Option<GamepadTrigger>;
+}
+
+/// A raw input of a [`ScriptedGamepad`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScriptedEvent {
+    Pressed(GamepadButton),
+    Axis(GamepadAxis, f32),
+}
+
+/// Replays a fixed list of inputs, axes going through [`AxisEdges`] like a
+/// real gamepad: stands in for one in tests and headless runs.
+#[derive(Default)]
+pub struct ScriptedGamepad {
+    events: VecDeque<ScriptedEvent>,
+    axes: AxisEdges,
+}
+
+impl ScriptedGamepad {
+    pub fn new(events: impl IntoIterator<Item = ScriptedEvent>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+            axes: AxisEdges::default(),
+        }
+    }
+
+    pub fn push(&mut self, event: ScriptedEvent) {
+        self.events.push_back(event);
+    }
+}
+
+impl GamepadSource for ScriptedGamepad {
+    fn next_trigger(&mut self) -> Option<GamepadTrigger> {
+        while let Some(event) = self.events.pop_front() {
+            let trigger = match event {
+                ScriptedEvent::Pressed(button) => Some(GamepadTrigger::Button(button)),
+                ScriptedEvent::Axis(axis, value) => self.axes.update(axis, value),
+            };
+
+            if trigger.is_some() {
+                return trigger;
+            }
+        }
+
+        None
+    }
+}
+
+/// Last trigger received, read by the learn mode.
+pub type GamepadMonitor = InputMonitor<GamepadTrigger>;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod desktop;
+#[cfg(not(target_arch = "wasm32"))]
+pub use desktop::DesktopGamepadInput as PlatformGamepadInput;
+
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+#[cfg(target_arch = "wasm32")]
+pub use web::WebGamepadInput as PlatformGamepadInput;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_trigger_once_per_push() {
+        use GamepadAxis::LeftStickX;
+
+        let mut gamepad = ScriptedGamepad::new([
+            ScriptedEvent::Axis(LeftStickX, 0.2),
+            ScriptedEvent::Axis(LeftStickX, 0.8),
+            ScriptedEvent::Axis(LeftStickX, 0.9),
+            ScriptedEvent::Axis(LeftStickX, 0.0),
+            ScriptedEvent::Axis(LeftStickX, 0.7),
+            // Da un lato all'altro senza passare dal centro
+            ScriptedEvent::Axis(LeftStickX, -0.6),
+            ScriptedEvent::Pressed(GamepadButton::South),
+        ]);
+
+        let triggers: Vec<_> = std::iter::from_fn(|| gamepad.next_trigger()).collect();
+        assert_eq!(triggers, [
+            GamepadTrigger::Axis { axis: LeftStickX, positive: true },
+            GamepadTrigger::Axis { axis: LeftStickX, positive: true },
+            GamepadTrigger::Axis { axis: LeftStickX, positive: false },
+            GamepadTrigger::Button(GamepadButton::South),
+        ]);
+    }
+}
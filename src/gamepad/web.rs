@@ -0,0 +1,23 @@
+use super::GamepadMonitor;
+
+/// Stub: the Gamepad API is not wired up yet.
+#[derive(Default)]
+pub struct WebGamepadInput {
+    monitor: GamepadMonitor,
+}
+
+impl WebGamepadInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&mut self) {
+    }
+
+    pub fn monitor(&self) -> GamepadMonitor {
+        self.monitor.clone()
+    }
+
+    pub fn set_repaint_context(&mut self, _ctx: egui::Context) {
+    }
+}
@@ -79,7 +79,7 @@ pub fn show_file_label_with_click(ui: &mut egui::Ui, sound: &Sound) {
                         let array = Uint8Array::from(data.as_slice());
 
                         let bag = BlobPropertyBag::new();
-                        bag.set_type("audio/wav");
+                        bag.set_type(crate::audio::mime_type(path));
 
                         let blob = Blob::new_with_u8_array_sequence_and_options(
                             &js_sys::Array::of1(&array.into()),
@@ -20,6 +20,10 @@ pub const REMOVE_EMOJI: &str = "🗑";     // Rimuovi
 pub const PLAY_EMOJI: &str = "▶";       // Riproduci
 pub const STOP_EMOJI: &str = "⏹";       // Ferma
 pub const EDIT_EMOJI: &str = "✏";       // Modifica
+pub const RECORD_EMOJI: &str = "🔴";     // Registra
+pub const MUTE_EMOJI: &str = "🔇";       // Muto
+pub const VOLUME_EMOJI: &str = "🔊";     // Volume
+pub const HOTKEYS_EMOJI: &str = "⌨";     // Hotkey
 
 /*
 fn load_icon(
@@ -1,7 +1,10 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod sound;
-pub use sound::Sound;
+pub use sound::{RetriggerPolicy, Sound, TriggerMode};
+
+mod board;
+pub use board::{Board, Boards};
 
 mod app;
 pub use app::Noisette;
@@ -19,4 +22,8 @@ pub use helpers::*;
 
 pub mod audio;
 
-pub mod shortcut;
\ No newline at end of file
+pub mod shortcut;
+
+pub mod midi;
+
+pub mod gamepad;
\ No newline at end of file
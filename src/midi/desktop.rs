@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use midir::{MidiInput, MidiInputConnection, MidiOutput, MidiOutputConnection};
+
+use super::feedback::feedback_message;
+use super::{MidiMessage, MidiMonitor, MidiTrigger};
+
+const CLIENT_NAME: &str = "noisette";
+
+/// Connection to a MIDI input port. Triggers go to the trigger loop through
+/// the channel returned by [`DesktopMidiInput::take_events`].
+pub struct DesktopMidiInput {
+    connection: Option<MidiInputConnection<()>>,
+    port: Option<String>,
+    sender: Sender<MidiMessage>,
+    events: Option<Receiver<MidiMessage>>,
+    monitor: MidiMonitor,
+    repaint: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl Default for DesktopMidiInput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DesktopMidiInput {
+    pub fn new() -> Self {
+        let (sender, events) = mpsc::channel();
+
+        Self {
+            connection: None,
+            port: None,
+            sender,
+            events: Some(events),
+            monitor: MidiMonitor::default(),
+            repaint: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn port_names() -> Vec<String> {
+        let Ok(input) = MidiInput::new(CLIENT_NAME) else {
+            return Vec::new();
+        };
+
+        input
+            .ports()
+            .iter()
+            .filter_map(|port| input.port_name(port).ok())
+            .collect()
+    }
+
+    pub fn port(&self) -> Option<&str> {
+        self.port.as_deref()
+    }
+
+    /// Connects to the port called `name`, or disconnects with `None`.
+    pub fn connect(&mut self, name: Option<&str>) -> Result<(), String> {
+        self.connection = None;
+        self.port = None;
+
+        let Some(name) = name else {
+            return Ok(());
+        };
+
+        let input = MidiInput::new(CLIENT_NAME).map_err(|e| format!("MIDI unavailable: {e}"))?;
+        let port = input
+            .ports()
+            .into_iter()
+            .find(|port| input.port_name(port).is_ok_and(|port_name| port_name == name))
+            .ok_or_else(|| format!("MIDI port not found: {name}"))?;
+
+        let sender = self.sender.clone();
+        let monitor = self.monitor.clone();
+        let repaint = Arc::clone(&self.repaint);
+
+        let connection = input
+            .connect(&port, "noisette-input", move |_, bytes, _| {
+                let Some(message) = MidiMessage::parse(bytes) else {
+                    return;
+                };
+
+                monitor.record(message.trigger);
+                let _ = sender.send(message);
+
+                // La modalita' "learn" deve vedere subito il messaggio
+                if let Ok(repaint) = repaint.lock() && let Some(ctx) = repaint.as_ref() {
+                    ctx.request_repaint();
+                }
+            }, ())
+            .map_err(|e| format!("Cannot open MIDI port {name}: {e}"))?;
+
+        self.connection = Some(connection);
+        self.port = Some(name.to_string());
+        Ok(())
+    }
+
+    /// The receiving end of the trigger channel; only the first call gets it.
+    pub fn take_events(&mut self) -> Option<Receiver<MidiMessage>> {
+        self.events.take()
+    }
+
+    pub fn monitor(&self) -> MidiMonitor {
+        self.monitor.clone()
+    }
+
+    pub fn set_repaint_context(&mut self, ctx: egui::Context) {
+        if let Ok(mut repaint) = self.repaint.lock() {
+            *repaint = Some(ctx);
+        }
+    }
+}
+
+/// Connection to a MIDI output port, used to light up the controller pads.
+#[derive(Default)]
+pub struct DesktopMidiOutput {
+    connection: Option<MidiOutputConnection>,
+    port: Option<String>,
+    /// Last value sent to each pad, so only changes go over the wire.
+    sent: HashMap<MidiTrigger, u8>,
+}
+
+impl DesktopMidiOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn port_names() -> Vec<String> {
+        let Ok(output) = MidiOutput::new(CLIENT_NAME) else {
+            return Vec::new();
+        };
+
+        output
+            .ports()
+            .iter()
+            .filter_map(|port| output.port_name(port).ok())
+            .collect()
+    }
+
+    pub fn port(&self) -> Option<&str> {
+        self.port.as_deref()
+    }
+
+    /// Connects to the port called `name`, or disconnects with `None`.
+    pub fn connect(&mut self, name: Option<&str>) -> Result<(), String> {
+        self.clear();
+        self.connection = None;
+        self.port = None;
+
+        let Some(name) = name else {
+            return Ok(());
+        };
+
+        let output = MidiOutput::new(CLIENT_NAME).map_err(|e| format!("MIDI unavailable: {e}"))?;
+        let port = output
+            .ports()
+            .into_iter()
+            .find(|port| output.port_name(port).is_ok_and(|port_name| port_name == name))
+            .ok_or_else(|| format!("MIDI port not found: {name}"))?;
+
+        let connection = output
+            .connect(&port, "noisette-output")
+            .map_err(|e| format!("Cannot open MIDI port {name}: {e}"))?;
+
+        self.connection = Some(connection);
+        self.port = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Sends the pads whose value changed and turns off the ones no longer bound.
+    pub fn update(&mut self, values: &HashMap<MidiTrigger, u8>) {
+        let Some(connection) = self.connection.as_mut() else {
+            return;
+        };
+
+        for (trigger, value) in values {
+            if self.sent.get(trigger) != Some(value) {
+                let _ = connection.send(&feedback_message(*trigger, *value));
+                self.sent.insert(*trigger, *value);
+            }
+        }
+
+        self.sent.retain(|trigger, _| {
+            let bound = values.contains_key(trigger);
+            if !bound {
+                let _ = connection.send(&feedback_message(*trigger, 0));
+            }
+            bound
+        });
+    }
+
+    /// Turns off every pad lit so far.
+    fn clear(&mut self) {
+        if let Some(connection) = self.connection.as_mut() {
+            for trigger in self.sent.keys() {
+                let _ = connection.send(&feedback_message(*trigger, 0));
+            }
+        }
+        self.sent.clear();
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::time::Duration;
+
+    use midir::os::unix::VirtualOutput;
+
+    use super::*;
+
+    #[test]
+    #[ignore = "needs a MIDI sequencer, e.g. ALSA's /dev/snd/seq"]
+    fn receives_triggers_from_a_virtual_port() {
+        let output = MidiOutput::new("noisette-test").unwrap();
+        let mut virtual_port = output.create_virtual("noisette-loopback").unwrap();
+
+        let name = DesktopMidiInput::port_names()
+            .into_iter()
+            .find(|name| name.contains("noisette-loopback"))
+            .expect("the virtual port is listed");
+
+        let mut input = DesktopMidiInput::new();
+        input.connect(Some(&name)).unwrap();
+        let events = input.take_events().unwrap();
+
+        virtual_port.send(&[0x90, 60, 0]).unwrap();
+        virtual_port.send(&[0x91, 60, 90]).unwrap();
+
+        let message = events.recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(message, MidiMessage {
+            trigger: MidiTrigger::Note { channel: 1, note: 60 },
+            value: 90,
+        });
+        assert_eq!(input.monitor().take(), Some(message.trigger));
+    }
+}
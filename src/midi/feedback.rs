@@ -0,0 +1,195 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+// Su wasm `std::time::Instant` va in panic; sul desktop e' lo stesso tipo
+use web_time::Instant;
+
+use crate::Sound;
+use crate::audio::interface::PlaybackState;
+
+use super::MidiTrigger;
+
+/// What a pad bound to a sound shows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadState {
+    Idle,
+    Playing,
+    /// The sound has no file, or the file is gone.
+    Missing,
+}
+
+impl PadState {
+    pub fn of(sound: &Sound, playback: &PlaybackState, files: &mut FileCache) -> PadState {
+        let has_audio = sound.generator.is_some()
+            || sound.data.is_some()
+            || sound.path.as_deref().is_some_and(|path| files.exists(path));
+
+        if !has_audio {
+            PadState::Missing
+        } else if playback.is_playing(Some(sound)) {
+            PadState::Playing
+        } else {
+            PadState::Idle
+        }
+    }
+}
+
+/// How long a file is assumed to exist, or to be missing, before checking again.
+const FILE_RECHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Remembers which sound files exist, so the pads do not hit the disk every
+/// frame. A new path is checked right away.
+#[derive(Default)]
+pub struct FileCache {
+    checked: HashMap<String, (bool, Instant)>,
+}
+
+impl FileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn exists(&mut self, path: &str) -> bool {
+        let now = Instant::now();
+
+        match self.checked.get(path) {
+            Some((exists, at)) if now.duration_since(*at) < FILE_RECHECK_INTERVAL => *exists,
+            _ => {
+                let exists = Path::new(path).exists();
+                self.checked.insert(path.to_string(), (exists, now));
+                exists
+            }
+        }
+    }
+}
+
+/// Velocities (or CC values) a controller maps to the colour of each state.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct FeedbackProfile {
+    pub name: String,
+    pub idle: u8,
+    pub playing: u8,
+    pub missing: u8,
+}
+
+impl Default for FeedbackProfile {
+    fn default() -> Self {
+        Self::generic()
+    }
+}
+
+impl FeedbackProfile {
+    /// Dim when idle, full when playing, off when the file is missing.
+    pub fn generic() -> Self {
+        Self {
+            name: "Generic".to_string(),
+            idle: 32,
+            playing: 127,
+            missing: 0,
+        }
+    }
+
+    /// Akai APC mini: green, red, yellow.
+    pub fn apc_mini() -> Self {
+        Self {
+            name: "Akai APC mini".to_string(),
+            idle: 1,
+            playing: 3,
+            missing: 5,
+        }
+    }
+
+    /// Novation Launchpad (original, S and Mini): green, red, amber.
+    pub fn launchpad() -> Self {
+        Self {
+            name: "Novation Launchpad".to_string(),
+            idle: 0x3C,
+            playing: 0x0F,
+            missing: 0x3F,
+        }
+    }
+
+    pub fn presets() -> [FeedbackProfile; 3] {
+        [Self::generic(), Self::apc_mini(), Self::launchpad()]
+    }
+
+    pub fn value(&self, state: PadState) -> u8 {
+        match state {
+            PadState::Idle => self.idle,
+            PadState::Playing => self.playing,
+            PadState::Missing => self.missing,
+        }
+    }
+}
+
+/// The value every bound pad should show. When two sounds share a pad the
+/// first one wins, as in the trigger loop.
+pub fn pad_values(
+    sounds: &[impl Borrow<Sound>],
+    playback: &PlaybackState,
+    profile: &FeedbackProfile,
+    files: &mut FileCache,
+) -> HashMap<MidiTrigger, u8> {
+    let mut values = HashMap::new();
+
+    for sound in sounds.iter().map(Borrow::borrow) {
+        if let Some(trigger) = sound.midi {
+            values
+                .entry(trigger)
+                .or_insert_with(|| profile.value(PadState::of(sound, playback, files)));
+        }
+    }
+
+    // Le voci dei suoni rimossi non servono piu'
+    files.checked.retain(|path, _| sounds.iter().any(|sound| sound.borrow().path.as_deref() == Some(path.as_str())));
+
+    values
+}
+
+/// The raw message that sets a pad: note-on for notes, CC for controllers.
+pub fn feedback_message(trigger: MidiTrigger, value: u8) -> [u8; 3] {
+    let value = value.min(127);
+
+    match trigger {
+        MidiTrigger::Note { channel, note } => [0x90 | (channel & 0x0F), note, value],
+        MidiTrigger::ControlChange { channel, controller } => [0xB0 | (channel & 0x0F), controller, value],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_status_is_cached() {
+        let path = std::env::temp_dir().join(format!("noisette-pad-{}.wav", std::process::id()));
+        let path_string = path.display().to_string();
+        std::fs::write(&path, b"RIFF").unwrap();
+
+        let mut files = FileCache::new();
+        assert!(files.exists(&path_string));
+
+        // Cancellato dopo il controllo: il pad non cambia fino al prossimo
+        std::fs::remove_file(&path).unwrap();
+        assert!(files.exists(&path_string));
+        assert!(!FileCache::new().exists(&path_string));
+    }
+
+    #[test]
+    fn pads_show_missing_files() {
+        let trigger = MidiTrigger::Note { channel: 0, note: 36 };
+        let sound = Sound {
+            path: Some("/nonexistent/noisette.wav".to_string()),
+            midi: Some(trigger),
+            ..Sound::default()
+        };
+        let profile = FeedbackProfile::apc_mini();
+
+        let values = pad_values(&[sound], &PlaybackState::default(), &profile, &mut FileCache::new());
+        assert_eq!(values.get(&trigger), Some(&profile.missing));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::shortcut::monitor::InputMonitor;
+
+pub mod feedback;
+
+/// A MIDI message a sound can be bound to.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MidiTrigger {
+    Note { channel: u8, note: u8 },
+    ControlChange { channel: u8, controller: u8 },
+}
+
+impl std::fmt::Display for MidiTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+        match self {
+            MidiTrigger::Note { channel, note } => {
+                let octave = i32::from(*note / 12) - 1;
+                write!(f, "Note {}{} (ch {})", NAMES[usize::from(*note % 12)], octave, channel + 1)
+            }
+            MidiTrigger::ControlChange { channel, controller } => {
+                write!(f, "CC {} (ch {})", controller, channel + 1)
+            }
+        }
+    }
+}
+
+/// A trigger received from a controller, with its velocity or CC value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MidiMessage {
+    pub trigger: MidiTrigger,
+    pub value: u8,
+}
+
+impl MidiMessage {
+    /// Parses a raw MIDI message. Only note-on and control change with a
+    /// non-zero value are triggers: a note-on with velocity 0 is a note-off
+    /// and pads send CC 0 when released.
+    pub fn parse(bytes: &[u8]) -> Option<MidiMessage> {
+        let [status, data1, data2, ..] = *bytes else {
+            return None;
+        };
+        let channel = status & 0x0F;
+
+        let trigger = match status & 0xF0 {
+            0x90 if data2 > 0 => MidiTrigger::Note { channel, note: data1 },
+            0xB0 if data2 > 0 => MidiTrigger::ControlChange { channel, controller: data1 },
+            _ => return None,
+        };
+
+        Some(MidiMessage { trigger, value: data2 })
+    }
+
+    /// The velocity or CC value as a gain between 0 and 1.
+    pub fn gain(&self) -> f32 {
+        f32::from(self.value) / 127.0
+    }
+}
+
+/// Last trigger received, read by the learn mode.
+pub type MidiMonitor = InputMonitor<MidiTrigger>;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod desktop;
+#[cfg(not(target_arch = "wasm32"))]
+pub use desktop::{DesktopMidiInput as PlatformMidiInput, DesktopMidiOutput as PlatformMidiOutput};
+
+#[cfg(target_arch = "wasm32")]
+pub mod web;
+#[cfg(target_arch = "wasm32")]
+pub use web::{WebMidiInput as PlatformMidiInput, WebMidiOutput as PlatformMidiOutput};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn note_on_is_a_trigger() {
+        let message = MidiMessage::parse(&[0x90, 60, 100]);
+
+        assert_eq!(message, Some(MidiMessage {
+            trigger: MidiTrigger::Note { channel: 0, note: 60 },
+            value: 100,
+        }));
+    }
+
+    #[test]
+    fn note_on_with_velocity_zero_is_a_note_off() {
+        assert_eq!(MidiMessage::parse(&[0x90, 60, 0]), None);
+        assert_eq!(MidiMessage::parse(&[0x80, 60, 64]), None);
+    }
+
+    #[test]
+    fn control_change_is_a_trigger_until_released() {
+        let message = MidiMessage::parse(&[0xB0, 7, 127]);
+
+        assert_eq!(message.map(|m| m.trigger), Some(MidiTrigger::ControlChange { channel: 0, controller: 7 }));
+        assert_eq!(message.map(|m| m.gain()), Some(1.0));
+        assert_eq!(MidiMessage::parse(&[0xB0, 7, 0]), None);
+    }
+
+    #[test]
+    fn short_and_running_status_buffers_are_ignored() {
+        assert_eq!(MidiMessage::parse(&[]), None);
+        assert_eq!(MidiMessage::parse(&[0x90, 60]), None);
+        // Running status: mancano i byte di stato, i dati da soli non bastano
+        assert_eq!(MidiMessage::parse(&[60, 100]), None);
+        assert_eq!(MidiMessage::parse(&[60, 100, 0]), None);
+    }
+
+    #[test]
+    fn channel_comes_from_the_low_nibble() {
+        let message = MidiMessage::parse(&[0x9F, 36, 1]);
+
+        assert_eq!(message.map(|m| m.trigger), Some(MidiTrigger::Note { channel: 15, note: 36 }));
+    }
+}
@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use super::{MidiMonitor, MidiTrigger};
+
+/// Stub: Web MIDI is not wired up yet.
+#[derive(Default)]
+pub struct WebMidiInput {
+    monitor: MidiMonitor,
+}
+
+impl WebMidiInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn port_names() -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn port(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn connect(&mut self, name: Option<&str>) -> Result<(), String> {
+        match name {
+            Some(_) => Err("MIDI input is not supported on the web".to_string()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn monitor(&self) -> MidiMonitor {
+        self.monitor.clone()
+    }
+
+    pub fn set_repaint_context(&mut self, _ctx: egui::Context) {
+    }
+}
+
+/// Stub: Web MIDI is not wired up yet.
+#[derive(Default)]
+pub struct WebMidiOutput;
+
+impl WebMidiOutput {
+    pub fn new() -> Self {
+        Self
+    }
+
+    pub fn port_names() -> Vec<String> {
+        Vec::new()
+    }
+
+    pub fn port(&self) -> Option<&str> {
+        None
+    }
+
+    pub fn connect(&mut self, name: Option<&str>) -> Result<(), String> {
+        match name {
+            Some(_) => Err("MIDI output is not supported on the web".to_string()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn update(&mut self, _values: &HashMap<MidiTrigger, u8>) {
+    }
+}
@@ -0,0 +1,93 @@
+use crate::shortcut::keycodes::SerializableKeycode;
+
+/// How much the volume hotkeys change the master volume.
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// App-level actions that can be bound to a global hotkey.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AppAction {
+    /// Panic button: stops every sound.
+    StopAll,
+    ToggleMute,
+    VolumeUp,
+    VolumeDown,
+    /// Arms or disarms every other hotkey.
+    ToggleHotkeys,
+    NextBoard,
+    PreviousBoard,
+}
+
+impl AppAction {
+    pub const ALL: [AppAction; 7] = [
+        AppAction::StopAll,
+        AppAction::ToggleMute,
+        AppAction::VolumeUp,
+        AppAction::VolumeDown,
+        AppAction::ToggleHotkeys,
+        AppAction::NextBoard,
+        AppAction::PreviousBoard,
+    ];
+}
+
+impl std::fmt::Display for AppAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            AppAction::StopAll => "Stop all",
+            AppAction::ToggleMute => "Mute / unmute",
+            AppAction::VolumeUp => "Volume up",
+            AppAction::VolumeDown => "Volume down",
+            AppAction::ToggleHotkeys => "Hotkeys on / off",
+            AppAction::NextBoard => "Next board",
+            AppAction::PreviousBoard => "Previous board",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// The hotkey bound to each [`AppAction`].
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct ActionShortcuts {
+    pub stop_all: Option<Vec<SerializableKeycode>>,
+    pub toggle_mute: Option<Vec<SerializableKeycode>>,
+    pub volume_up: Option<Vec<SerializableKeycode>>,
+    pub volume_down: Option<Vec<SerializableKeycode>>,
+    pub toggle_hotkeys: Option<Vec<SerializableKeycode>>,
+    pub next_board: Option<Vec<SerializableKeycode>>,
+    pub previous_board: Option<Vec<SerializableKeycode>>,
+}
+
+impl ActionShortcuts {
+    pub fn get(&self, action: AppAction) -> Option<&Vec<SerializableKeycode>> {
+        match action {
+            AppAction::StopAll => self.stop_all.as_ref(),
+            AppAction::ToggleMute => self.toggle_mute.as_ref(),
+            AppAction::VolumeUp => self.volume_up.as_ref(),
+            AppAction::VolumeDown => self.volume_down.as_ref(),
+            AppAction::ToggleHotkeys => self.toggle_hotkeys.as_ref(),
+            AppAction::NextBoard => self.next_board.as_ref(),
+            AppAction::PreviousBoard => self.previous_board.as_ref(),
+        }
+    }
+
+    pub fn get_mut(&mut self, action: AppAction) -> &mut Option<Vec<SerializableKeycode>> {
+        match action {
+            AppAction::StopAll => &mut self.stop_all,
+            AppAction::ToggleMute => &mut self.toggle_mute,
+            AppAction::VolumeUp => &mut self.volume_up,
+            AppAction::VolumeDown => &mut self.volume_down,
+            AppAction::ToggleHotkeys => &mut self.toggle_hotkeys,
+            AppAction::NextBoard => &mut self.next_board,
+            AppAction::PreviousBoard => &mut self.previous_board,
+        }
+    }
+
+    /// Every bound action with its hotkey.
+    pub fn iter(&self) -> impl Iterator<Item = (AppAction, &Vec<SerializableKeycode>)> {
+        AppAction::ALL
+            .into_iter()
+            .filter_map(|action| self.get(action).map(|shortcut| (action, shortcut)))
+    }
+}
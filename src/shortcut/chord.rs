@@ -0,0 +1,116 @@
+use crate::shortcut::keycodes::SerializableKeycode;
+
+/// `true` when every key of the chord is held and the held modifiers are
+/// exactly the chord's modifiers, so `A` does not fire while `Ctrl` is down.
+/// Extra non-modifier keys are allowed: they are disambiguated by
+/// [`most_specific`].
+pub fn chord_matches(chord: &[SerializableKeycode], pressed: &[SerializableKeycode]) -> bool {
+    if chord.is_empty() || !chord.iter().all(|k| pressed.iter().any(|p| k.matches(p))) {
+        return false;
+    }
+
+    pressed
+        .iter()
+        .filter(|p| p.is_modifier())
+        .all(|p| chord.iter().any(|k| k.matches(p)))
+}
+
+/// `true` when the chord is held now but was not at the previous poll. One of
+/// its keys must have just gone down: releasing `Ctrl` while holding `A` does
+/// not fire `A`.
+pub fn chord_just_pressed(
+    chord: &[SerializableKeycode],
+    pressed: &[SerializableKeycode],
+    previous: &[SerializableKeycode],
+) -> bool {
+    let newly_pressed = chord
+        .iter()
+        .any(|k| !previous.iter().any(|p| k.matches(p)));
+    newly_pressed && !chord_matches(chord, previous) && chord_matches(chord, pressed)
+}
+
+/// `true` when a key other than a modifier went down since the previous poll.
+pub fn any_key_just_pressed(pressed: &[SerializableKeycode], previous: &[SerializableKeycode]) -> bool {
+    pressed
+        .iter()
+        .any(|key| !key.is_modifier() && !previous.contains(key))
+}
+
+/// Replaces left/right modifiers with their side-agnostic variant, so the
+/// shortcut fires with either side.
+pub fn side_agnostic(chord: &[SerializableKeycode]) -> Vec<SerializableKeycode> {
+    let mut keys = Vec::with_capacity(chord.len());
+
+    for key in chord {
+        let key = key.generic().unwrap_or_else(|| key.clone());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    keys
+}
+
+/// Shortcut to store once the recorder has captured `keys`.
+pub fn recorded_chord(keys: &[SerializableKeycode], side_agnostic_modifiers: bool) -> Vec<SerializableKeycode> {
+    if side_agnostic_modifiers {
+        side_agnostic(keys)
+    } else {
+        keys.to_vec()
+    }
+}
+
+/// Picks the matching chord with the most keys; on a tie the first one wins.
+pub fn most_specific<'a, T>(
+    candidates: impl IntoIterator<Item = &'a T>,
+    chord: impl Fn(&T) -> &[SerializableKeycode],
+) -> Option<&'a T> {
+    candidates.into_iter().fold(None, |best: Option<&'a T>, candidate| match best {
+        Some(best) if chord(best).len() >= chord(candidate).len() => Some(best),
+        _ => Some(candidate),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SerializableKeycode::*;
+
+    #[test]
+    fn plain_key_does_not_fire_with_ctrl_held() {
+        assert!(chord_matches(&[A], &[A]));
+        assert!(!chord_matches(&[A], &[LControl, A]));
+    }
+
+    #[test]
+    fn most_specific_chord_wins() {
+        let chords = [vec![A], vec![Ctrl, A]];
+        assert_eq!(most_specific(&chords, |chord| chord), Some(&chords[1]));
+
+        // Con A e B premuti scattano sia `A` che `A+B`: vince il secondo
+        let chords = [vec![A], vec![A, B]];
+        let matching = chords.iter().filter(|chord| chord_matches(chord, &[A, B]));
+        assert_eq!(most_specific(matching, |chord| chord), Some(&chords[1]));
+    }
+
+    #[test]
+    fn extra_non_modifier_keys_are_allowed() {
+        assert!(chord_matches(&[Ctrl, A], &[LControl, A, B]));
+        assert!(chord_matches(&[A], &[A, Space]));
+    }
+
+    #[test]
+    fn releasing_ctrl_is_not_a_new_press() {
+        assert!(!chord_just_pressed(&[A], &[A], &[LControl, A]));
+        assert!(chord_just_pressed(&[A], &[A], &[]));
+        assert!(!chord_just_pressed(&[A], &[A], &[A]));
+    }
+
+    #[test]
+    fn side_agnostic_modifiers_match_either_side() {
+        let chord = side_agnostic(&[LControl, RControl, A]);
+
+        assert_eq!(chord, vec![Ctrl, A]);
+        assert!(chord_matches(&chord, &[RControl, A]));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::shortcut::action::AppAction;
+use crate::shortcut::chord::chord_matches;
+use crate::shortcut::keycodes::SerializableKeycode;
+
+/// Who a shortcut belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShortcutOwner {
+    /// Index into `Noisette::sounds`.
+    Sound(usize),
+    ToggleRecording,
+    Action(AppAction),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both shortcuts fire on the same keys.
+    Duplicate,
+    /// Holding the longer shortcut also holds the shorter one, which fires
+    /// first while the keys are being pressed.
+    Overlap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub first: ShortcutOwner,
+    pub second: ShortcutOwner,
+    pub kind: ConflictKind,
+}
+
+impl Conflict {
+    pub fn involves(&self, owner: ShortcutOwner) -> bool {
+        self.first == owner || self.second == owner
+    }
+
+    /// The owner on the other side of the conflict.
+    pub fn other(&self, owner: ShortcutOwner) -> ShortcutOwner {
+        if self.first == owner { self.second } else { self.first }
+    }
+}
+
+pub fn conflict_kind(a: &[SerializableKeycode], b: &[SerializableKeycode]) -> Option<ConflictKind> {
+    match (chord_matches(a, b), chord_matches(b, a)) {
+        (true, true) => Some(ConflictKind::Duplicate),
+        (true, false) | (false, true) => Some(ConflictKind::Overlap),
+        (false, false) => None,
+    }
+}
+
+/// Like [`conflict_kind`] for multi-step shortcuts: the earlier steps must be
+/// the same chord, and a sequence conflicts with its own prefix.
+pub fn sequence_conflict_kind(a: &[Vec<SerializableKeycode>], b: &[Vec<SerializableKeycode>]) -> Option<ConflictKind> {
+    let common = a.len().min(b.len());
+    if common == 0 {
+        return None;
+    }
+
+    let same_prefix = a[..common - 1]
+        .iter()
+        .zip(&b[..common - 1])
+        .all(|(a, b)| conflict_kind(a, b) == Some(ConflictKind::Duplicate));
+    if !same_prefix {
+        return None;
+    }
+
+    let last = conflict_kind(&a[common - 1], &b[common - 1])?;
+    Some(if a.len() == b.len() { last } else { ConflictKind::Overlap })
+}
+
+/// Every pair of shortcuts that conflict, in the order they are given. Each
+/// shortcut is the list of its steps.
+pub fn find_conflicts(shortcuts: &[(ShortcutOwner, Vec<Vec<SerializableKeycode>>)]) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+
+    for (i, (first, a)) in shortcuts.iter().enumerate() {
+        for (second, b) in &shortcuts[i + 1..] {
+            if let Some(kind) = sequence_conflict_kind(a, b) {
+                conflicts.push(Conflict {
+                    first: *first,
+                    second: *second,
+                    kind,
+                });
+            }
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SerializableKeycode::*;
+
+    fn steps(chords: &[&[SerializableKeycode]]) -> Vec<Vec<SerializableKeycode>> {
+        chords.iter().map(|chord| chord.to_vec()).collect()
+    }
+
+    #[test]
+    fn identical_chords_are_duplicates() {
+        assert_eq!(conflict_kind(&[Ctrl, Key1], &[Ctrl, Key1]), Some(ConflictKind::Duplicate));
+        assert_eq!(conflict_kind(&[Key1, Ctrl], &[Ctrl, Key1]), Some(ConflictKind::Duplicate));
+        assert_eq!(conflict_kind(&[Ctrl, Key1], &[Ctrl, Key2]), None);
+    }
+
+    #[test]
+    fn a_chord_inside_another_overlaps() {
+        // Tenendo A+B si tiene anche A
+        assert_eq!(conflict_kind(&[A], &[A, B]), Some(ConflictKind::Overlap));
+        assert_eq!(conflict_kind(&[A, B], &[A]), Some(ConflictKind::Overlap));
+        // Un modificatore in piu' invece distingue le due scorciatoie
+        assert_eq!(conflict_kind(&[A], &[Ctrl, A]), None);
+    }
+
+    #[test]
+    fn sequences_conflict_with_their_prefix() {
+        let leader = steps(&[&[F13]]);
+        let three = steps(&[&[F13], &[Key3]]);
+        let three_four = steps(&[&[F13], &[Key3], &[Key4]]);
+
+        assert_eq!(sequence_conflict_kind(&leader, &three), Some(ConflictKind::Overlap));
+        assert_eq!(sequence_conflict_kind(&three_four, &three), Some(ConflictKind::Overlap));
+        assert_eq!(sequence_conflict_kind(&three, &three), Some(ConflictKind::Duplicate));
+        assert_eq!(sequence_conflict_kind(&[], &three), None);
+    }
+
+    #[test]
+    fn sequences_apart_after_the_first_step_do_not_conflict() {
+        let three = steps(&[&[F13], &[Key3]]);
+
+        assert_eq!(sequence_conflict_kind(&three, &steps(&[&[F13], &[Key4]])), None);
+        assert_eq!(sequence_conflict_kind(&three, &steps(&[&[F14], &[Key3]])), None);
+        // Il passo comune deve coincidere, non solo sovrapporsi
+        assert_eq!(sequence_conflict_kind(&three, &steps(&[&[F13, A], &[Key3]])), None);
+        assert_eq!(sequence_conflict_kind(&steps(&[&[F14]]), &three), None);
+    }
+
+    #[test]
+    fn every_conflicting_pair_is_reported_in_order() {
+        let shortcuts = vec![
+            (ShortcutOwner::Sound(0), steps(&[&[Ctrl, Key1]])),
+            (ShortcutOwner::Action(AppAction::StopAll), steps(&[&[Ctrl, Key1]])),
+            (ShortcutOwner::Sound(1), steps(&[&[Key2]])),
+            (ShortcutOwner::ToggleRecording, steps(&[&[Key2], &[Key3]])),
+            (ShortcutOwner::Sound(2), steps(&[&[Key4]])),
+        ];
+
+        assert_eq!(
+            find_conflicts(&shortcuts),
+            [
+                Conflict {
+                    first: ShortcutOwner::Sound(0),
+                    second: ShortcutOwner::Action(AppAction::StopAll),
+                    kind: ConflictKind::Duplicate,
+                },
+                Conflict {
+                    first: ShortcutOwner::Sound(1),
+                    second: ShortcutOwner::ToggleRecording,
+                    kind: ConflictKind::Overlap,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn the_other_side_of_a_conflict() {
+        let conflict = Conflict {
+            first: ShortcutOwner::Sound(3),
+            second: ShortcutOwner::ToggleRecording,
+            kind: ConflictKind::Duplicate,
+        };
+
+        assert!(conflict.involves(ShortcutOwner::Sound(3)));
+        assert!(!conflict.involves(ShortcutOwner::Sound(0)));
+        assert_eq!(conflict.other(ShortcutOwner::Sound(3)), ShortcutOwner::ToggleRecording);
+        assert_eq!(conflict.other(ShortcutOwner::ToggleRecording), ShortcutOwner::Sound(3));
+    }
+}
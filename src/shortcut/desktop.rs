@@ -2,11 +2,16 @@ use std::sync::{Arc, Mutex};
 
 use device_query::{DeviceQuery, DeviceState};
 
+use crate::shortcut::ShortcutBackendKind;
+use crate::shortcut::chord::{any_key_just_pressed, chord_just_pressed, chord_matches};
 use crate::shortcut::keycodes::SerializableKeycode;
 use crate::shortcut::interface::ShortcutListener;
 
 pub struct DesktopShortcutListener {
-    pressed_keys: Arc<Mutex<Vec<SerializableKeycode>>>
+    pressed_keys: Arc<Mutex<Vec<SerializableKeycode>>>,
+    previous_keys: Vec<SerializableKeycode>,
+    /// Opened once: on X11 each one is a new connection to the display.
+    device_state: DeviceState,
 }
 
 impl DesktopShortcutListener {
@@ -15,22 +20,9 @@ impl DesktopShortcutListener {
         //let keys_clone = Arc::clone(&keys);
 
         Self {
-            pressed_keys: keys
-        }
-    }
-
-    pub fn update(&mut self) {
-        let device_state = DeviceState::new();
-        let keys_clone = Arc::clone(&self.pressed_keys);
-
-        let current_keys: Vec<SerializableKeycode> = device_state
-            .get_keys()
-            .into_iter()
-            .map(|k| SerializableKeycode::from(k))
-            .collect();
-
-        if let Ok(mut keys_lock) = keys_clone.lock() {
-            *keys_lock = current_keys.clone();
+            pressed_keys: keys,
+            previous_keys: Vec::new(),
+            device_state: DeviceState::new(),
         }
     }
 
@@ -43,12 +35,127 @@ impl DesktopShortcutListener {
     }
 }
 
+/// The listener for the trigger loop. Falls back to device_query when the
+/// evdev devices cannot be read.
+pub fn listener(backend: ShortcutBackendKind) -> Box<dyn ShortcutListener> {
+    #[cfg(target_os = "linux")]
+    if backend == ShortcutBackendKind::Evdev {
+        match super::evdev::EvdevShortcutListener::new() {
+            Ok(listener) => return Box::new(listener),
+            Err(error) => eprintln!("{error}"),
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    let _ = backend;
+
+    Box::new(DesktopShortcutListener::new())
+}
+
+thread_local! {
+    /// Kept by the shortcut recorder, which polls every frame.
+    static RECORDER_DEVICE_STATE: DeviceState = DeviceState::new();
+}
+
+/// Keys and bindable mouse buttons held according to `backend`, for the
+/// shortcut recorder.
+pub fn pressed_inputs_with(backend: ShortcutBackendKind) -> Vec<SerializableKeycode> {
+    let device_query = || RECORDER_DEVICE_STATE.with(pressed_inputs);
+
+    match backend {
+        #[cfg(target_os = "linux")]
+        ShortcutBackendKind::Evdev => super::evdev::shared_pressed_inputs().unwrap_or_else(|_| device_query()),
+        _ => device_query(),
+    }
+}
+
+/// Keys and bindable mouse buttons currently held.
+pub fn pressed_inputs(device_state: &DeviceState) -> Vec<SerializableKeycode> {
+    let mut pressed: Vec<SerializableKeycode> = device_state
+        .get_keys()
+        .into_iter()
+        .map(SerializableKeycode::from)
+        .map(physical_key)
+        .collect();
+
+    pressed.extend(SerializableKeycode::mouse_buttons(&device_state.get_mouse()));
+    pressed
+}
+
+/// Windows reports the virtual key the layout puts on a key, not the key
+/// itself: its scan code tells where it is. device_query already reads the
+/// physical key on Linux and macOS.
+///
+/// Shortcuts saved before keys were stored by position hold the virtual key,
+/// so they go through here once when loaded, see `Noisette::new`.
+#[cfg(target_os = "windows")]
+pub fn physical_key(key: SerializableKeycode) -> SerializableKeycode {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VK_TO_VSC};
+
+    let virtual_key = match &key {
+        SerializableKeycode::Key0 => 0x30,
+        SerializableKeycode::Key1 => 0x31,
+        SerializableKeycode::Key2 => 0x32,
+        SerializableKeycode::Key3 => 0x33,
+        SerializableKeycode::Key4 => 0x34,
+        SerializableKeycode::Key5 => 0x35,
+        SerializableKeycode::Key6 => 0x36,
+        SerializableKeycode::Key7 => 0x37,
+        SerializableKeycode::Key8 => 0x38,
+        SerializableKeycode::Key9 => 0x39,
+        SerializableKeycode::Semicolon => 0xBA,
+        SerializableKeycode::Equal => 0xBB,
+        SerializableKeycode::Comma => 0xBC,
+        SerializableKeycode::Minus => 0xBD,
+        SerializableKeycode::Dot => 0xBE,
+        SerializableKeycode::Slash => 0xBF,
+        SerializableKeycode::Grave => 0xC0,
+        SerializableKeycode::LeftBracket => 0xDB,
+        SerializableKeycode::BackSlash => 0xDC,
+        SerializableKeycode::RightBracket => 0xDD,
+        SerializableKeycode::Apostrophe => 0xDE,
+        // Le lettere hanno il codice ASCII maiuscolo
+        letter if letter.name().len() == 1 && letter.name().as_bytes()[0].is_ascii_uppercase() => {
+            u32::from(letter.name().as_bytes()[0])
+        }
+        _ => return key,
+    };
+
+    // SAFETY: MapVirtualKeyW only reads the active keyboard layout and
+    // accepts any code, returning 0 for the unknown ones.
+    let scan_code = unsafe { MapVirtualKeyW(virtual_key, MAPVK_VK_TO_VSC) };
+    SerializableKeycode::from_scan_code(scan_code).unwrap_or(key)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn physical_key(key: SerializableKeycode) -> SerializableKeycode {
+    key
+}
+
 impl ShortcutListener for DesktopShortcutListener {
-    fn is_pressed(&self, shortcut: &Vec<SerializableKeycode>) -> bool {
+    fn update(&mut self) {
+        let keys_clone = Arc::clone(&self.pressed_keys);
+        let current_keys = pressed_inputs(&self.device_state);
+
+        if let Ok(mut keys_lock) = keys_clone.lock() {
+            // Tieni lo stato precedente per riconoscere i fronti di pressione
+            self.previous_keys = std::mem::replace(&mut *keys_lock, current_keys);
+        }
+    }
+
+    fn is_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
         if let Ok(pressed) = self.pressed_keys.lock() {
-            shortcut.iter().all(|k| pressed.contains(k))
+            chord_matches(shortcut, &pressed)
         } else {
             false // non riesce a ottenere il lock? -> non premuto
         }
     }
+
+    fn just_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+        chord_just_pressed(shortcut, &self.get_pressed_keys(), &self.previous_keys)
+    }
+
+    fn any_key_just_pressed(&self) -> bool {
+        any_key_just_pressed(&self.get_pressed_keys(), &self.previous_keys)
+    }
 }
@@ -0,0 +1,257 @@
+use std::cell::RefCell;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use evdev::Device;
+
+use crate::shortcut::chord::{any_key_just_pressed, chord_just_pressed, chord_matches};
+use crate::shortcut::interface::ShortcutListener;
+use crate::shortcut::keycodes::SerializableKeycode;
+
+const INPUT_DIR: &str = "/dev/input";
+
+/// How often `/dev/input` is listed again, to pick up keyboards plugged in later.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// The keys held on every input device, read straight from the kernel: unlike
+/// device_query this works under Wayland.
+pub struct EvdevKeyboard {
+    /// Devices to read instead of the whole of `/dev/input`.
+    paths: Option<Vec<PathBuf>>,
+    devices: Vec<(PathBuf, Device)>,
+    last_scan: Instant,
+}
+
+impl EvdevKeyboard {
+    /// Opens every device in `/dev/input` with keys or buttons. Fails with
+    /// what to do when none of them can be read.
+    pub fn open() -> Result<Self, String> {
+        Self::with_paths(None)
+    }
+
+    /// Reads only `paths`, e.g. a virtual uinput keyboard.
+    pub fn open_paths(paths: Vec<PathBuf>) -> Result<Self, String> {
+        Self::with_paths(Some(paths))
+    }
+
+    fn with_paths(paths: Option<Vec<PathBuf>>) -> Result<Self, String> {
+        let mut keyboard = Self {
+            paths,
+            devices: Vec::new(),
+            last_scan: Instant::now(),
+        };
+        keyboard.scan()?;
+        Ok(keyboard)
+    }
+
+    /// Opens the devices that appeared since the last scan.
+    fn scan(&mut self) -> Result<(), String> {
+        self.last_scan = Instant::now();
+
+        let paths = match &self.paths {
+            Some(paths) => paths.clone(),
+            None => event_devices().map_err(|error| format!("Cannot list {INPUT_DIR}: {error}"))?,
+        };
+
+        let mut denied = None;
+
+        for path in paths {
+            if self.devices.iter().any(|(open, _)| *open == path) {
+                continue;
+            }
+
+            match Device::open(&path) {
+                Ok(device) => {
+                    let has_keys = device.supported_keys().is_some_and(|keys| keys.iter().next().is_some());
+                    if has_keys {
+                        self.devices.push((path, device));
+                    }
+                }
+                Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                    denied.get_or_insert(path);
+                }
+                // Dispositivi spariti nel frattempo o non evdev
+                Err(_) => {}
+            }
+        }
+
+        match denied {
+            Some(path) if self.devices.is_empty() => Err(permission_diagnostic(&path)),
+            _ if self.devices.is_empty() => Err(format!("No keyboard found in {INPUT_DIR}")),
+            _ => Ok(()),
+        }
+    }
+
+    /// Keys and mouse buttons held on any of the devices.
+    pub fn pressed_inputs(&mut self) -> Vec<SerializableKeycode> {
+        if self.last_scan.elapsed() > RESCAN_INTERVAL {
+            let _ = self.scan();
+        }
+
+        let mut pressed = Vec::new();
+
+        // Un dispositivo scollegato da' errore: viene riaperto se ricompare
+        self.devices.retain(|(_, device)| {
+            let Ok(keys) = device.get_key_state() else {
+                return false;
+            };
+
+            for key in keys.iter().map(SerializableKeycode::from) {
+                if !matches!(key, SerializableKeycode::Unknown(_)) && !pressed.contains(&key) {
+                    pressed.push(key);
+                }
+            }
+            true
+        });
+
+        pressed
+    }
+}
+
+/// The `eventN` nodes in `/dev/input`.
+fn event_devices() -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(INPUT_DIR)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("event"))
+        })
+        .collect();
+
+    paths.sort();
+    Ok(paths)
+}
+
+fn permission_diagnostic(path: &Path) -> String {
+    format!(
+        "Cannot read {}: permission denied. Add your user to the `input` group \
+         (`sudo usermod -aG input $USER`) and log in again.",
+        path.display()
+    )
+}
+
+pub struct EvdevShortcutListener {
+    keyboard: EvdevKeyboard,
+    pressed_keys: Vec<SerializableKeycode>,
+    previous_keys: Vec<SerializableKeycode>,
+}
+
+impl EvdevShortcutListener {
+    pub fn new() -> Result<Self, String> {
+        Ok(Self::with_keyboard(EvdevKeyboard::open()?))
+    }
+
+    pub fn with_keyboard(keyboard: EvdevKeyboard) -> Self {
+        Self {
+            keyboard,
+            pressed_keys: Vec::new(),
+            previous_keys: Vec::new(),
+        }
+    }
+
+    pub fn get_pressed_keys(&self) -> Vec<SerializableKeycode> {
+        self.pressed_keys.clone()
+    }
+}
+
+impl ShortcutListener for EvdevShortcutListener {
+    fn update(&mut self) {
+        let current_keys = self.keyboard.pressed_inputs();
+        self.previous_keys = std::mem::replace(&mut self.pressed_keys, current_keys);
+    }
+
+    fn is_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+        chord_matches(shortcut, &self.pressed_keys)
+    }
+
+    fn just_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+        chord_just_pressed(shortcut, &self.pressed_keys, &self.previous_keys)
+    }
+
+    fn any_key_just_pressed(&self) -> bool {
+        any_key_just_pressed(&self.pressed_keys, &self.previous_keys)
+    }
+}
+
+thread_local! {
+    /// Devices kept open by the shortcut recorder, which polls every frame,
+    /// or why they could not be opened and when.
+    static RECORDER_KEYBOARD: RefCell<Option<Result<EvdevKeyboard, (String, Instant)>>> = const { RefCell::new(None) };
+}
+
+/// Keys held according to evdev, for the shortcut recorder on the UI thread.
+/// After a failure `/dev/input` is tried again only every [`RESCAN_INTERVAL`].
+pub fn shared_pressed_inputs() -> Result<Vec<SerializableKeycode>, String> {
+    RECORDER_KEYBOARD.with(|keyboard| {
+        let mut keyboard = keyboard.borrow_mut();
+        let retry = match &*keyboard {
+            None => true,
+            Some(Err((_, failed_at))) => failed_at.elapsed() > RESCAN_INTERVAL,
+            Some(Ok(_)) => false,
+        };
+        if retry {
+            *keyboard = Some(EvdevKeyboard::open().map_err(|error| (error, Instant::now())));
+        }
+
+        match keyboard.as_mut() {
+            Some(Ok(keyboard)) => Ok(keyboard.pressed_inputs()),
+            Some(Err((error, _))) => Err(error.clone()),
+            None => Ok(Vec::new()),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread::sleep;
+
+    use evdev::uinput::VirtualDevice;
+    use evdev::{AttributeSet, EventType, InputEvent, KeyCode};
+
+    use super::*;
+
+    fn key_event(key: KeyCode, value: i32) -> InputEvent {
+        InputEvent::new(EventType::KEY.0, key.code(), value)
+    }
+
+    #[test]
+    #[ignore = "needs write access to /dev/uinput and read access to /dev/input"]
+    fn reads_chords_from_a_virtual_keyboard() {
+        let mut keys = AttributeSet::<KeyCode>::new();
+        keys.insert(KeyCode::KEY_LEFTCTRL);
+        keys.insert(KeyCode::KEY_A);
+
+        let mut device = VirtualDevice::builder().unwrap()
+            .name("noisette-test-keyboard")
+            .with_keys(&keys).unwrap()
+            .build().unwrap();
+        let paths = device.enumerate_dev_nodes_blocking().unwrap()
+            .collect::<io::Result<Vec<_>>>().unwrap();
+
+        let mut listener = EvdevShortcutListener::with_keyboard(EvdevKeyboard::open_paths(paths).unwrap());
+        let chord = [SerializableKeycode::LControl, SerializableKeycode::A];
+
+        listener.update();
+        assert!(!listener.any_key_just_pressed());
+
+        device.emit(&[key_event(KeyCode::KEY_LEFTCTRL, 1), key_event(KeyCode::KEY_A, 1)]).unwrap();
+        // Il kernel aggiorna lo stato dei tasti in modo asincrono
+        sleep(Duration::from_millis(100));
+
+        listener.update();
+        assert!(listener.just_pressed(&chord));
+        assert!(listener.is_pressed(&chord));
+
+        listener.update();
+        assert!(!listener.just_pressed(&chord));
+
+        device.emit(&[key_event(KeyCode::KEY_A, 0), key_event(KeyCode::KEY_LEFTCTRL, 0)]).unwrap();
+        sleep(Duration::from_millis(100));
+
+        listener.update();
+        assert!(!listener.is_pressed(&chord));
+    }
+}
@@ -0,0 +1,270 @@
+use std::time::Duration;
+
+// Su wasm `std::time::Instant` va in panic; sul desktop e' lo stesso tipo
+use web_time::Instant;
+
+use crate::shortcut::keycodes::SerializableKeycode;
+use crate::shortcut::trigger::Binding;
+use crate::sound::TriggerMode;
+
+/// How long the gestures last.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GestureTiming {
+    /// Longest pause between the two presses of a double tap.
+    pub double_tap: Duration,
+    /// How long a key has to be held to be a long press.
+    pub long_press: Duration,
+}
+
+/// State of one chord bound to gestures.
+struct ChordState {
+    chord: Vec<SerializableKeycode>,
+    held_since: Option<Instant>,
+    long_fired: bool,
+    taps: u32,
+    last_release: Option<Instant>,
+}
+
+/// Tells taps, double taps and long presses of the same chord apart.
+///
+/// A tap only fires once the double-tap window has passed, unless nothing is
+/// bound to the double tap of that chord.
+#[derive(Default)]
+pub struct GestureTracker {
+    states: Vec<ChordState>,
+}
+
+impl GestureTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` while a chord is held or a tap may still become a double tap.
+    pub fn in_progress(&self) -> bool {
+        self.states
+            .iter()
+            .any(|state| state.held_since.is_some() || state.taps > 0)
+    }
+
+    /// Feeds one poll of the keyboard and returns the bindings to fire.
+    /// Only the first step of each binding is looked at.
+    pub fn poll<'a>(
+        &mut self,
+        bindings: &'a [Binding],
+        now: Instant,
+        timing: GestureTiming,
+        is_pressed: impl Fn(&[SerializableKeycode]) -> bool,
+    ) -> Vec<&'a Binding> {
+        let chords: Vec<&Vec<SerializableKeycode>> = bindings
+            .iter()
+            .filter_map(|binding| binding.steps.first())
+            .collect();
+
+        self.states.retain(|state| chords.contains(&&state.chord));
+        for chord in &chords {
+            if !self.states.iter().any(|state| state.chord == **chord) {
+                self.states.push(ChordState {
+                    chord: (*chord).clone(),
+                    held_since: None,
+                    long_fired: false,
+                    taps: 0,
+                    last_release: None,
+                });
+            }
+        }
+
+        let mut fired = Vec::new();
+
+        for state in &mut self.states {
+            let chord = &state.chord.clone();
+            let on_chord = |mode: TriggerMode| {
+                bindings
+                    .iter()
+                    .filter(move |binding| binding.mode == mode && binding.steps.first() == Some(chord))
+            };
+            let has_double_tap = on_chord(TriggerMode::DoubleTap).next().is_some();
+            let has_long_press = on_chord(TriggerMode::LongPress).next().is_some();
+
+            // La seconda pressione non e' arrivata in tempo: era un tap
+            let expired = state
+                .last_release
+                .is_some_and(|release| now.duration_since(release) > timing.double_tap);
+            if state.held_since.is_none() && state.taps > 0 && expired {
+                state.taps = 0;
+                fired.extend(on_chord(TriggerMode::Tap));
+            }
+
+            let gesture = match (state.held_since, is_pressed(chord)) {
+                (None, true) => {
+                    state.held_since = Some(now);
+                    state.long_fired = false;
+                    None
+                }
+                (Some(since), true) => {
+                    if has_long_press && !state.long_fired && now.duration_since(since) >= timing.long_press {
+                        state.long_fired = true;
+                        state.taps = 0;
+                        Some(TriggerMode::LongPress)
+                    } else {
+                        None
+                    }
+                }
+                // Rilasciato dopo una pressione lunga: e' gia' scattata
+                (Some(_), false) if state.long_fired => {
+                    state.held_since = None;
+                    None
+                }
+                (Some(_), false) => {
+                    state.held_since = None;
+                    state.taps += 1;
+                    state.last_release = Some(now);
+
+                    if state.taps >= 2 || !has_double_tap {
+                        let gesture = if state.taps >= 2 { TriggerMode::DoubleTap } else { TriggerMode::Tap };
+                        state.taps = 0;
+                        Some(gesture)
+                    } else {
+                        None
+                    }
+                }
+                (None, false) => None,
+            };
+
+            if let Some(gesture) = gesture {
+                fired.extend(on_chord(gesture));
+            }
+        }
+
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shortcut::trigger::TriggerAction;
+
+    use super::*;
+    use SerializableKeycode::*;
+
+    const TIMING: GestureTiming = GestureTiming {
+        double_tap: Duration::from_millis(300),
+        long_press: Duration::from_millis(500),
+    };
+
+    fn bindings(modes: &[TriggerMode]) -> Vec<Binding> {
+        modes
+            .iter()
+            .map(|mode| Binding {
+                steps: vec![vec![F13]],
+                action: TriggerAction::ToggleRecording,
+                mode: *mode,
+            })
+            .collect()
+    }
+
+    /// Polls with F13 `held` or not, `at` milliseconds in, and returns the
+    /// gestures that fired.
+    fn poll(tracker: &mut GestureTracker, bindings: &[Binding], start: Instant, at: u64, held: bool) -> Vec<TriggerMode> {
+        let now = start + Duration::from_millis(at);
+        tracker
+            .poll(bindings, now, TIMING, |chord| held && chord == [F13])
+            .into_iter()
+            .map(|binding| binding.mode)
+            .collect()
+    }
+
+    #[test]
+    fn a_tap_waits_for_the_double_tap_window() {
+        let bindings = bindings(&[TriggerMode::Tap, TriggerMode::DoubleTap]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 50, false), []);
+        assert!(tracker.in_progress());
+        // Esattamente alla fine della finestra puo' ancora arrivare la seconda pressione
+        assert_eq!(poll(&mut tracker, &bindings, start, 350, false), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 351, false), [TriggerMode::Tap]);
+        assert!(!tracker.in_progress());
+    }
+
+    #[test]
+    fn a_tap_fires_on_release_without_a_double_tap_binding() {
+        let bindings = bindings(&[TriggerMode::Tap]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 50, false), [TriggerMode::Tap]);
+        assert!(!tracker.in_progress());
+    }
+
+    #[test]
+    fn two_quick_taps_are_a_double_tap() {
+        let bindings = bindings(&[TriggerMode::Tap, TriggerMode::DoubleTap]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 50, false), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 350, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 400, false), [TriggerMode::DoubleTap]);
+
+        // Il primo tap e' stato assorbito dal doppio
+        assert_eq!(poll(&mut tracker, &bindings, start, 1000, false), []);
+        assert!(!tracker.in_progress());
+    }
+
+    #[test]
+    fn a_late_second_press_is_two_taps() {
+        let bindings = bindings(&[TriggerMode::Tap, TriggerMode::DoubleTap]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 50, false), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 400, true), [TriggerMode::Tap]);
+        assert_eq!(poll(&mut tracker, &bindings, start, 450, false), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 800, false), [TriggerMode::Tap]);
+    }
+
+    #[test]
+    fn a_long_press_fires_once_while_held() {
+        let bindings = bindings(&[TriggerMode::Tap, TriggerMode::DoubleTap, TriggerMode::LongPress]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 499, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 500, true), [TriggerMode::LongPress]);
+        assert_eq!(poll(&mut tracker, &bindings, start, 2000, true), []);
+        assert!(tracker.in_progress());
+
+        // Il rilascio dopo una pressione lunga non e' un tap
+        assert_eq!(poll(&mut tracker, &bindings, start, 2100, false), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 3000, false), []);
+        assert!(!tracker.in_progress());
+    }
+
+    #[test]
+    fn holding_without_a_long_press_binding_is_still_a_tap() {
+        let bindings = bindings(&[TriggerMode::Tap]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 2000, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 2100, false), [TriggerMode::Tap]);
+    }
+
+    #[test]
+    fn a_short_press_is_not_a_long_press() {
+        let bindings = bindings(&[TriggerMode::Tap, TriggerMode::LongPress]);
+        let mut tracker = GestureTracker::new();
+        let start = Instant::now();
+
+        assert_eq!(poll(&mut tracker, &bindings, start, 0, true), []);
+        assert_eq!(poll(&mut tracker, &bindings, start, 499, false), [TriggerMode::Tap]);
+        assert_eq!(poll(&mut tracker, &bindings, start, 600, false), []);
+    }
+}
@@ -1,5 +1,12 @@
 use crate::shortcut::keycodes::SerializableKeycode;
 
 pub trait ShortcutListener {
-    fn is_pressed(&self, shortcut: &Vec<SerializableKeycode>) -> bool;
-}
\ No newline at end of file
+    /// Reads the held keys again; [`ShortcutListener::just_pressed`] compares
+    /// them with the previous update.
+    fn update(&mut self);
+    fn is_pressed(&self, shortcut: &[SerializableKeycode]) -> bool;
+    /// `true` only on the update where the shortcut went from released to pressed.
+    fn just_pressed(&self, shortcut: &[SerializableKeycode]) -> bool;
+    /// `true` when a key other than a modifier went down since the last update.
+    fn any_key_just_pressed(&self) -> bool;
+}
@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A physical key, named after its place on a US QWERTY keyboard like the DOM
+/// `KeyboardEvent.code`: `Q` is the key left of `W` on every layout.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum SerializableKeycode {
     Key0,
     Key1,
@@ -59,6 +62,10 @@ pub enum SerializableKeycode {
     F18,
     F19,
     F20,
+    F21,
+    F22,
+    F23,
+    F24,
     Escape,
     Space,
     LControl,
@@ -73,6 +80,14 @@ pub enum SerializableKeycode {
     ROption,
     LMeta,
     RMeta,
+    /// Either Control key.
+    Ctrl,
+    /// Either Shift key.
+    Shift,
+    /// Either Alt / Option key.
+    Alt,
+    /// Either Super / Windows / Command key.
+    Super,
     Enter,
     Up,
     Down,
@@ -115,10 +130,30 @@ pub enum SerializableKeycode {
     Comma,
     Dot,
     Slash,
+    /// The ISO key between left Shift and Z.
+    IntlBackslash,
+    /// The Japanese `ろ` key.
+    IntlRo,
+    /// The Japanese `¥` key.
+    IntlYen,
+    PrintScreen,
+    ScrollLock,
+    Pause,
+    NumLock,
+    ContextMenu,
+    MediaPlayPause,
+    MediaStop,
+    MediaNext,
+    MediaPrevious,
+    VolumeUp,
+    VolumeDown,
+    VolumeMute,
+    MouseMiddle,
+    MouseBack,
+    MouseForward,
     Unknown(String),
 }
 
-#[cfg(target_arch = "wasm32")]
 impl From<egui::Key> for crate::shortcut::keycodes::SerializableKeycode {
     fn from(key: egui::Key) -> Self {
         match key {
@@ -185,11 +220,45 @@ impl From<egui::Key> for crate::shortcut::keycodes::SerializableKeycode {
             egui::Key::F10 => SerializableKeycode::F10,
             egui::Key::F11 => SerializableKeycode::F11,
             egui::Key::F12 => SerializableKeycode::F12,
+            egui::Key::F13 => SerializableKeycode::F13,
+            egui::Key::F14 => SerializableKeycode::F14,
+            egui::Key::F15 => SerializableKeycode::F15,
+            egui::Key::F16 => SerializableKeycode::F16,
+            egui::Key::F17 => SerializableKeycode::F17,
+            egui::Key::F18 => SerializableKeycode::F18,
+            egui::Key::F19 => SerializableKeycode::F19,
+            egui::Key::F20 => SerializableKeycode::F20,
+            egui::Key::F21 => SerializableKeycode::F21,
+            egui::Key::F22 => SerializableKeycode::F22,
+            egui::Key::F23 => SerializableKeycode::F23,
+            egui::Key::F24 => SerializableKeycode::F24,
+            egui::Key::Backtick => SerializableKeycode::Grave,
+            egui::Key::Minus => SerializableKeycode::Minus,
+            egui::Key::Equals => SerializableKeycode::Equal,
+            egui::Key::OpenBracket => SerializableKeycode::LeftBracket,
+            egui::Key::CloseBracket => SerializableKeycode::RightBracket,
+            egui::Key::Backslash => SerializableKeycode::BackSlash,
+            egui::Key::Semicolon => SerializableKeycode::Semicolon,
+            egui::Key::Quote => SerializableKeycode::Apostrophe,
+            egui::Key::Comma => SerializableKeycode::Comma,
+            egui::Key::Period => SerializableKeycode::Dot,
+            egui::Key::Slash => SerializableKeycode::Slash,
+            // Simboli che si ottengono con Shift: il tasto fisico e' quello del layout US,
+            // cosi' la shortcut registrata e' la stessa del desktop (Shift piu' il tasto)
+            egui::Key::Plus => SerializableKeycode::Equal,
+            egui::Key::Colon => SerializableKeycode::Semicolon,
+            egui::Key::Pipe => SerializableKeycode::BackSlash,
+            egui::Key::Questionmark => SerializableKeycode::Slash,
+            egui::Key::Exclamationmark => SerializableKeycode::Key1,
+            egui::Key::OpenCurlyBracket => SerializableKeycode::LeftBracket,
+            egui::Key::CloseCurlyBracket => SerializableKeycode::RightBracket,
             _ => SerializableKeycode::Unknown(format!("{:?}", key)),
         }
     }
 }
 
+/// device_query stops at F20 and has no media or ISO keys: those are only seen
+/// by the browser and the evdev backend.
 #[cfg(not(target_arch = "wasm32"))]
 impl From<device_query::Keycode> for crate::shortcut::keycodes::SerializableKeycode {
     fn from(key: device_query::Keycode) -> Self {
@@ -261,7 +330,12 @@ impl From<device_query::Keycode> for crate::shortcut::keycodes::SerializableKeyc
             device_query::Keycode::RShift => SerializableKeycode::RShift,
             device_query::Keycode::LAlt => SerializableKeycode::LAlt,
             device_query::Keycode::RAlt => SerializableKeycode::RAlt,
-            device_query::Keycode::LMeta => SerializableKeycode::Command,
+            device_query::Keycode::Command => SerializableKeycode::Command,
+            device_query::Keycode::RCommand => SerializableKeycode::RCommand,
+            device_query::Keycode::LOption => SerializableKeycode::LOption,
+            device_query::Keycode::ROption => SerializableKeycode::ROption,
+            device_query::Keycode::LMeta => SerializableKeycode::LMeta,
+            device_query::Keycode::RMeta => SerializableKeycode::RMeta,
 
             device_query::Keycode::Enter => SerializableKeycode::Enter,
             device_query::Keycode::Up => SerializableKeycode::Up,
@@ -294,10 +368,11 @@ impl From<device_query::Keycode> for crate::shortcut::keycodes::SerializableKeyc
             device_query::Keycode::NumpadMultiply => SerializableKeycode::NumpadMultiply,
             device_query::Keycode::NumpadDecimal => SerializableKeycode::NumpadDecimal,
             device_query::Keycode::NumpadEnter => SerializableKeycode::NumpadEnter,
+            device_query::Keycode::NumpadEquals => SerializableKeycode::NumpadEquals,
 
-            device_query::Keycode::NumpadEquals => SerializableKeycode::Equal,
             device_query::Keycode::Grave => SerializableKeycode::Grave,
             device_query::Keycode::Minus => SerializableKeycode::Minus,
+            device_query::Keycode::Equal => SerializableKeycode::Equal,
             device_query::Keycode::LeftBracket => SerializableKeycode::LeftBracket,
             device_query::Keycode::RightBracket => SerializableKeycode::RightBracket,
             device_query::Keycode::BackSlash => SerializableKeycode::BackSlash,
@@ -306,17 +381,872 @@ impl From<device_query::Keycode> for crate::shortcut::keycodes::SerializableKeyc
             device_query::Keycode::Comma => SerializableKeycode::Comma,
             device_query::Keycode::Dot => SerializableKeycode::Dot,
             device_query::Keycode::Slash => SerializableKeycode::Slash,
+        }
+    }
+}
+
+/// Linux input key codes, as read from `/dev/input` by the evdev backend.
+#[cfg(target_os = "linux")]
+impl From<evdev::KeyCode> for SerializableKeycode {
+    fn from(key: evdev::KeyCode) -> Self {
+        // Il kernel numera il blocco di scrittura come gli scan code PC
+        if let Some(typing) = SerializableKeycode::from_scan_code(u32::from(key.code())) {
+            return typing;
+        }
 
+        match key {
+            evdev::KeyCode::KEY_ESC => SerializableKeycode::Escape,
+            evdev::KeyCode::KEY_BACKSPACE => SerializableKeycode::Backspace,
+            evdev::KeyCode::KEY_TAB => SerializableKeycode::Tab,
+            evdev::KeyCode::KEY_ENTER => SerializableKeycode::Enter,
+            evdev::KeyCode::KEY_SPACE => SerializableKeycode::Space,
+            evdev::KeyCode::KEY_CAPSLOCK => SerializableKeycode::CapsLock,
+            evdev::KeyCode::KEY_LEFTCTRL => SerializableKeycode::LControl,
+            evdev::KeyCode::KEY_RIGHTCTRL => SerializableKeycode::RControl,
+            evdev::KeyCode::KEY_LEFTSHIFT => SerializableKeycode::LShift,
+            evdev::KeyCode::KEY_RIGHTSHIFT => SerializableKeycode::RShift,
+            evdev::KeyCode::KEY_LEFTALT => SerializableKeycode::LAlt,
+            evdev::KeyCode::KEY_RIGHTALT => SerializableKeycode::RAlt,
+            evdev::KeyCode::KEY_LEFTMETA => SerializableKeycode::LMeta,
+            evdev::KeyCode::KEY_RIGHTMETA => SerializableKeycode::RMeta,
+            evdev::KeyCode::KEY_F1 => SerializableKeycode::F1,
+            evdev::KeyCode::KEY_F2 => SerializableKeycode::F2,
+            evdev::KeyCode::KEY_F3 => SerializableKeycode::F3,
+            evdev::KeyCode::KEY_F4 => SerializableKeycode::F4,
+            evdev::KeyCode::KEY_F5 => SerializableKeycode::F5,
+            evdev::KeyCode::KEY_F6 => SerializableKeycode::F6,
+            evdev::KeyCode::KEY_F7 => SerializableKeycode::F7,
+            evdev::KeyCode::KEY_F8 => SerializableKeycode::F8,
+            evdev::KeyCode::KEY_F9 => SerializableKeycode::F9,
+            evdev::KeyCode::KEY_F10 => SerializableKeycode::F10,
+            evdev::KeyCode::KEY_F11 => SerializableKeycode::F11,
+            evdev::KeyCode::KEY_F12 => SerializableKeycode::F12,
+            evdev::KeyCode::KEY_F13 => SerializableKeycode::F13,
+            evdev::KeyCode::KEY_F14 => SerializableKeycode::F14,
+            evdev::KeyCode::KEY_F15 => SerializableKeycode::F15,
+            evdev::KeyCode::KEY_F16 => SerializableKeycode::F16,
+            evdev::KeyCode::KEY_F17 => SerializableKeycode::F17,
+            evdev::KeyCode::KEY_F18 => SerializableKeycode::F18,
+            evdev::KeyCode::KEY_F19 => SerializableKeycode::F19,
+            evdev::KeyCode::KEY_F20 => SerializableKeycode::F20,
+            evdev::KeyCode::KEY_F21 => SerializableKeycode::F21,
+            evdev::KeyCode::KEY_F22 => SerializableKeycode::F22,
+            evdev::KeyCode::KEY_F23 => SerializableKeycode::F23,
+            evdev::KeyCode::KEY_F24 => SerializableKeycode::F24,
+            evdev::KeyCode::KEY_UP => SerializableKeycode::Up,
+            evdev::KeyCode::KEY_DOWN => SerializableKeycode::Down,
+            evdev::KeyCode::KEY_LEFT => SerializableKeycode::Left,
+            evdev::KeyCode::KEY_RIGHT => SerializableKeycode::Right,
+            evdev::KeyCode::KEY_HOME => SerializableKeycode::Home,
+            evdev::KeyCode::KEY_END => SerializableKeycode::End,
+            evdev::KeyCode::KEY_PAGEUP => SerializableKeycode::PageUp,
+            evdev::KeyCode::KEY_PAGEDOWN => SerializableKeycode::PageDown,
+            evdev::KeyCode::KEY_INSERT => SerializableKeycode::Insert,
+            evdev::KeyCode::KEY_DELETE => SerializableKeycode::Delete,
+            evdev::KeyCode::KEY_KP0 => SerializableKeycode::Numpad0,
+            evdev::KeyCode::KEY_KP1 => SerializableKeycode::Numpad1,
+            evdev::KeyCode::KEY_KP2 => SerializableKeycode::Numpad2,
+            evdev::KeyCode::KEY_KP3 => SerializableKeycode::Numpad3,
+            evdev::KeyCode::KEY_KP4 => SerializableKeycode::Numpad4,
+            evdev::KeyCode::KEY_KP5 => SerializableKeycode::Numpad5,
+            evdev::KeyCode::KEY_KP6 => SerializableKeycode::Numpad6,
+            evdev::KeyCode::KEY_KP7 => SerializableKeycode::Numpad7,
+            evdev::KeyCode::KEY_KP8 => SerializableKeycode::Numpad8,
+            evdev::KeyCode::KEY_KP9 => SerializableKeycode::Numpad9,
+            evdev::KeyCode::KEY_KPMINUS => SerializableKeycode::NumpadSubtract,
+            evdev::KeyCode::KEY_KPPLUS => SerializableKeycode::NumpadAdd,
+            evdev::KeyCode::KEY_KPSLASH => SerializableKeycode::NumpadDivide,
+            evdev::KeyCode::KEY_KPASTERISK => SerializableKeycode::NumpadMultiply,
+            evdev::KeyCode::KEY_KPEQUAL => SerializableKeycode::NumpadEquals,
+            evdev::KeyCode::KEY_KPENTER => SerializableKeycode::NumpadEnter,
+            evdev::KeyCode::KEY_KPDOT => SerializableKeycode::NumpadDecimal,
+            evdev::KeyCode::KEY_RO => SerializableKeycode::IntlRo,
+            evdev::KeyCode::KEY_YEN => SerializableKeycode::IntlYen,
+            evdev::KeyCode::KEY_SYSRQ => SerializableKeycode::PrintScreen,
+            evdev::KeyCode::KEY_SCROLLLOCK => SerializableKeycode::ScrollLock,
+            evdev::KeyCode::KEY_PAUSE => SerializableKeycode::Pause,
+            evdev::KeyCode::KEY_NUMLOCK => SerializableKeycode::NumLock,
+            evdev::KeyCode::KEY_COMPOSE => SerializableKeycode::ContextMenu,
+            evdev::KeyCode::KEY_PLAYPAUSE => SerializableKeycode::MediaPlayPause,
+            evdev::KeyCode::KEY_STOPCD => SerializableKeycode::MediaStop,
+            evdev::KeyCode::KEY_NEXTSONG => SerializableKeycode::MediaNext,
+            evdev::KeyCode::KEY_PREVIOUSSONG => SerializableKeycode::MediaPrevious,
+            evdev::KeyCode::KEY_VOLUMEUP => SerializableKeycode::VolumeUp,
+            evdev::KeyCode::KEY_VOLUMEDOWN => SerializableKeycode::VolumeDown,
+            evdev::KeyCode::KEY_MUTE => SerializableKeycode::VolumeMute,
+            evdev::KeyCode::BTN_MIDDLE => SerializableKeycode::MouseMiddle,
+            evdev::KeyCode::BTN_SIDE => SerializableKeycode::MouseBack,
+            evdev::KeyCode::BTN_EXTRA => SerializableKeycode::MouseForward,
             other => SerializableKeycode::Unknown(format!("{:?}", other)),
         }
     }
 }
 
+impl SerializableKeycode {
+    /// The key for a DOM `KeyboardEvent.code`, which names the physical key
+    /// regardless of the keyboard layout.
+    pub fn from_dom_code(code: &str) -> SerializableKeycode {
+        match code {
+            "Digit0" => SerializableKeycode::Key0,
+            "Digit1" => SerializableKeycode::Key1,
+            "Digit2" => SerializableKeycode::Key2,
+            "Digit3" => SerializableKeycode::Key3,
+            "Digit4" => SerializableKeycode::Key4,
+            "Digit5" => SerializableKeycode::Key5,
+            "Digit6" => SerializableKeycode::Key6,
+            "Digit7" => SerializableKeycode::Key7,
+            "Digit8" => SerializableKeycode::Key8,
+            "Digit9" => SerializableKeycode::Key9,
+            "KeyA" => SerializableKeycode::A,
+            "KeyB" => SerializableKeycode::B,
+            "KeyC" => SerializableKeycode::C,
+            "KeyD" => SerializableKeycode::D,
+            "KeyE" => SerializableKeycode::E,
+            "KeyF" => SerializableKeycode::F,
+            "KeyG" => SerializableKeycode::G,
+            "KeyH" => SerializableKeycode::H,
+            "KeyI" => SerializableKeycode::I,
+            "KeyJ" => SerializableKeycode::J,
+            "KeyK" => SerializableKeycode::K,
+            "KeyL" => SerializableKeycode::L,
+            "KeyM" => SerializableKeycode::M,
+            "KeyN" => SerializableKeycode::N,
+            "KeyO" => SerializableKeycode::O,
+            "KeyP" => SerializableKeycode::P,
+            "KeyQ" => SerializableKeycode::Q,
+            "KeyR" => SerializableKeycode::R,
+            "KeyS" => SerializableKeycode::S,
+            "KeyT" => SerializableKeycode::T,
+            "KeyU" => SerializableKeycode::U,
+            "KeyV" => SerializableKeycode::V,
+            "KeyW" => SerializableKeycode::W,
+            "KeyX" => SerializableKeycode::X,
+            "KeyY" => SerializableKeycode::Y,
+            "KeyZ" => SerializableKeycode::Z,
+            "F1" => SerializableKeycode::F1,
+            "F2" => SerializableKeycode::F2,
+            "F3" => SerializableKeycode::F3,
+            "F4" => SerializableKeycode::F4,
+            "F5" => SerializableKeycode::F5,
+            "F6" => SerializableKeycode::F6,
+            "F7" => SerializableKeycode::F7,
+            "F8" => SerializableKeycode::F8,
+            "F9" => SerializableKeycode::F9,
+            "F10" => SerializableKeycode::F10,
+            "F11" => SerializableKeycode::F11,
+            "F12" => SerializableKeycode::F12,
+            "F13" => SerializableKeycode::F13,
+            "F14" => SerializableKeycode::F14,
+            "F15" => SerializableKeycode::F15,
+            "F16" => SerializableKeycode::F16,
+            "F17" => SerializableKeycode::F17,
+            "F18" => SerializableKeycode::F18,
+            "F19" => SerializableKeycode::F19,
+            "F20" => SerializableKeycode::F20,
+            "F21" => SerializableKeycode::F21,
+            "F22" => SerializableKeycode::F22,
+            "F23" => SerializableKeycode::F23,
+            "F24" => SerializableKeycode::F24,
+            "Escape" => SerializableKeycode::Escape,
+            "Space" => SerializableKeycode::Space,
+            "ControlLeft" => SerializableKeycode::LControl,
+            "ControlRight" => SerializableKeycode::RControl,
+            "ShiftLeft" => SerializableKeycode::LShift,
+            "ShiftRight" => SerializableKeycode::RShift,
+            "AltLeft" => SerializableKeycode::LAlt,
+            "AltRight" => SerializableKeycode::RAlt,
+            "MetaLeft" | "OSLeft" => SerializableKeycode::LMeta,
+            "MetaRight" | "OSRight" => SerializableKeycode::RMeta,
+            "Enter" => SerializableKeycode::Enter,
+            "ArrowUp" => SerializableKeycode::Up,
+            "ArrowDown" => SerializableKeycode::Down,
+            "ArrowLeft" => SerializableKeycode::Left,
+            "ArrowRight" => SerializableKeycode::Right,
+            "Backspace" => SerializableKeycode::Backspace,
+            "CapsLock" => SerializableKeycode::CapsLock,
+            "Tab" => SerializableKeycode::Tab,
+            "Home" => SerializableKeycode::Home,
+            "End" => SerializableKeycode::End,
+            "PageUp" => SerializableKeycode::PageUp,
+            "PageDown" => SerializableKeycode::PageDown,
+            "Insert" => SerializableKeycode::Insert,
+            "Delete" => SerializableKeycode::Delete,
+            "Numpad0" => SerializableKeycode::Numpad0,
+            "Numpad1" => SerializableKeycode::Numpad1,
+            "Numpad2" => SerializableKeycode::Numpad2,
+            "Numpad3" => SerializableKeycode::Numpad3,
+            "Numpad4" => SerializableKeycode::Numpad4,
+            "Numpad5" => SerializableKeycode::Numpad5,
+            "Numpad6" => SerializableKeycode::Numpad6,
+            "Numpad7" => SerializableKeycode::Numpad7,
+            "Numpad8" => SerializableKeycode::Numpad8,
+            "Numpad9" => SerializableKeycode::Numpad9,
+            "NumpadSubtract" => SerializableKeycode::NumpadSubtract,
+            "NumpadAdd" => SerializableKeycode::NumpadAdd,
+            "NumpadDivide" => SerializableKeycode::NumpadDivide,
+            "NumpadMultiply" => SerializableKeycode::NumpadMultiply,
+            "NumpadEqual" => SerializableKeycode::NumpadEquals,
+            "NumpadEnter" => SerializableKeycode::NumpadEnter,
+            "NumpadDecimal" => SerializableKeycode::NumpadDecimal,
+            "Backquote" => SerializableKeycode::Grave,
+            "Minus" => SerializableKeycode::Minus,
+            "Equal" => SerializableKeycode::Equal,
+            "BracketLeft" => SerializableKeycode::LeftBracket,
+            "BracketRight" => SerializableKeycode::RightBracket,
+            "Backslash" => SerializableKeycode::BackSlash,
+            "Semicolon" => SerializableKeycode::Semicolon,
+            "Quote" => SerializableKeycode::Apostrophe,
+            "Comma" => SerializableKeycode::Comma,
+            "Period" => SerializableKeycode::Dot,
+            "Slash" => SerializableKeycode::Slash,
+            "IntlBackslash" => SerializableKeycode::IntlBackslash,
+            "IntlRo" => SerializableKeycode::IntlRo,
+            "IntlYen" => SerializableKeycode::IntlYen,
+            "PrintScreen" => SerializableKeycode::PrintScreen,
+            "ScrollLock" => SerializableKeycode::ScrollLock,
+            "Pause" => SerializableKeycode::Pause,
+            "NumLock" => SerializableKeycode::NumLock,
+            "ContextMenu" => SerializableKeycode::ContextMenu,
+            "MediaPlayPause" => SerializableKeycode::MediaPlayPause,
+            "MediaStop" => SerializableKeycode::MediaStop,
+            "MediaTrackNext" => SerializableKeycode::MediaNext,
+            "MediaTrackPrevious" => SerializableKeycode::MediaPrevious,
+            // Chrome usava i nomi senza `Audio`
+            "AudioVolumeUp" | "VolumeUp" => SerializableKeycode::VolumeUp,
+            "AudioVolumeDown" | "VolumeDown" => SerializableKeycode::VolumeDown,
+            "AudioVolumeMute" | "VolumeMute" => SerializableKeycode::VolumeMute,
+            other => SerializableKeycode::Unknown(other.to_string()),
+        }
+    }
+
+    /// The key of the typing block at a PC scan code (set 1), the part of
+    /// the keyboard that changes between layouts.
+    pub fn from_scan_code(scan_code: u32) -> Option<SerializableKeycode> {
+        match scan_code {
+            0x02 => Some(SerializableKeycode::Key1),
+            0x03 => Some(SerializableKeycode::Key2),
+            0x04 => Some(SerializableKeycode::Key3),
+            0x05 => Some(SerializableKeycode::Key4),
+            0x06 => Some(SerializableKeycode::Key5),
+            0x07 => Some(SerializableKeycode::Key6),
+            0x08 => Some(SerializableKeycode::Key7),
+            0x09 => Some(SerializableKeycode::Key8),
+            0x0A => Some(SerializableKeycode::Key9),
+            0x0B => Some(SerializableKeycode::Key0),
+            0x0C => Some(SerializableKeycode::Minus),
+            0x0D => Some(SerializableKeycode::Equal),
+            0x10 => Some(SerializableKeycode::Q),
+            0x11 => Some(SerializableKeycode::W),
+            0x12 => Some(SerializableKeycode::E),
+            0x13 => Some(SerializableKeycode::R),
+            0x14 => Some(SerializableKeycode::T),
+            0x15 => Some(SerializableKeycode::Y),
+            0x16 => Some(SerializableKeycode::U),
+            0x17 => Some(SerializableKeycode::I),
+            0x18 => Some(SerializableKeycode::O),
+            0x19 => Some(SerializableKeycode::P),
+            0x1A => Some(SerializableKeycode::LeftBracket),
+            0x1B => Some(SerializableKeycode::RightBracket),
+            0x1E => Some(SerializableKeycode::A),
+            0x1F => Some(SerializableKeycode::S),
+            0x20 => Some(SerializableKeycode::D),
+            0x21 => Some(SerializableKeycode::F),
+            0x22 => Some(SerializableKeycode::G),
+            0x23 => Some(SerializableKeycode::H),
+            0x24 => Some(SerializableKeycode::J),
+            0x25 => Some(SerializableKeycode::K),
+            0x26 => Some(SerializableKeycode::L),
+            0x27 => Some(SerializableKeycode::Semicolon),
+            0x28 => Some(SerializableKeycode::Apostrophe),
+            0x29 => Some(SerializableKeycode::Grave),
+            0x2B => Some(SerializableKeycode::BackSlash),
+            0x2C => Some(SerializableKeycode::Z),
+            0x2D => Some(SerializableKeycode::X),
+            0x2E => Some(SerializableKeycode::C),
+            0x2F => Some(SerializableKeycode::V),
+            0x30 => Some(SerializableKeycode::B),
+            0x31 => Some(SerializableKeycode::N),
+            0x32 => Some(SerializableKeycode::M),
+            0x33 => Some(SerializableKeycode::Comma),
+            0x34 => Some(SerializableKeycode::Dot),
+            0x35 => Some(SerializableKeycode::Slash),
+            0x56 => Some(SerializableKeycode::IntlBackslash),
+            _ => None,
+        }
+    }
+
+    /// Mouse buttons held in `state`. device_query numbers them differently on
+    /// each platform, and on X11 it only sees the middle button: 4 and 5 are
+    /// the scroll wheel there.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn mouse_buttons(state: &device_query::MouseState) -> Vec<SerializableKeycode> {
+        let buttons: &[(usize, SerializableKeycode)] = if cfg!(target_os = "linux") {
+            &[(2, SerializableKeycode::MouseMiddle)]
+        } else {
+            &[
+                (3, SerializableKeycode::MouseMiddle),
+                (4, SerializableKeycode::MouseBack),
+                (5, SerializableKeycode::MouseForward),
+            ]
+        };
+
+        buttons
+            .iter()
+            .filter(|(index, _)| state.button_pressed.get(*index).copied().unwrap_or(false))
+            .map(|(_, button)| button.clone())
+            .collect()
+    }
+
+    /// The shortcut key for an egui pointer button, if it can be bound.
+    pub fn from_pointer_button(button: egui::PointerButton) -> Option<SerializableKeycode> {
+        match button {
+            egui::PointerButton::Middle => Some(SerializableKeycode::MouseMiddle),
+            egui::PointerButton::Extra1 => Some(SerializableKeycode::MouseBack),
+            egui::PointerButton::Extra2 => Some(SerializableKeycode::MouseForward),
+            egui::PointerButton::Primary | egui::PointerButton::Secondary => None,
+        }
+    }
+
+    pub fn is_mouse(&self) -> bool {
+        matches!(
+            self,
+            SerializableKeycode::MouseMiddle
+                | SerializableKeycode::MouseBack
+                | SerializableKeycode::MouseForward
+        )
+    }
+
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self,
+            SerializableKeycode::LControl
+                | SerializableKeycode::RControl
+                | SerializableKeycode::LShift
+                | SerializableKeycode::RShift
+                | SerializableKeycode::LAlt
+                | SerializableKeycode::RAlt
+                | SerializableKeycode::Command
+                | SerializableKeycode::RCommand
+                | SerializableKeycode::LOption
+                | SerializableKeycode::ROption
+                | SerializableKeycode::LMeta
+                | SerializableKeycode::RMeta
+                | SerializableKeycode::Ctrl
+                | SerializableKeycode::Shift
+                | SerializableKeycode::Alt
+                | SerializableKeycode::Super
+        )
+    }
+
+    /// The side-agnostic variant of a left/right modifier.
+    pub fn generic(&self) -> Option<SerializableKeycode> {
+        match self {
+            SerializableKeycode::LControl | SerializableKeycode::RControl => Some(SerializableKeycode::Ctrl),
+            SerializableKeycode::LShift | SerializableKeycode::RShift => Some(SerializableKeycode::Shift),
+            SerializableKeycode::LAlt
+            | SerializableKeycode::RAlt
+            | SerializableKeycode::LOption
+            | SerializableKeycode::ROption => Some(SerializableKeycode::Alt),
+            SerializableKeycode::LMeta
+            | SerializableKeycode::RMeta
+            | SerializableKeycode::Command
+            | SerializableKeycode::RCommand => Some(SerializableKeycode::Super),
+            _ => None,
+        }
+    }
+
+    /// Whether this key of a shortcut is satisfied by a physically pressed key.
+    pub fn matches(&self, pressed: &SerializableKeycode) -> bool {
+        if self == pressed || pressed.generic().as_ref() == Some(self) {
+            return true;
+        }
+
+        // Le shortcut registrate prima di LMeta salvavano il tasto Meta sinistro come Command
+        *self == SerializableKeycode::Command && *pressed == SerializableKeycode::LMeta
+    }
+}
+
+/// Name of the Super / Windows / Command key on this platform.
+const SUPER_NAME: &str = if cfg!(target_os = "macos") {
+    "⌘"
+} else if cfg!(target_os = "windows") {
+    "Win"
+} else {
+    "Super"
+};
+const LEFT_SUPER_NAME: &str = if cfg!(target_os = "macos") {
+    "LMeta"
+} else if cfg!(target_os = "windows") {
+    "LWin"
+} else {
+    "LSuper"
+};
+const RIGHT_SUPER_NAME: &str = if cfg!(target_os = "macos") {
+    "RMeta"
+} else if cfg!(target_os = "windows") {
+    "RWin"
+} else {
+    "RSuper"
+};
+const ALT_NAME: &str = if cfg!(target_os = "macos") { "⌥" } else { "Alt" };
+
+/// Spellings the parser accepts besides the displayed names and the variant
+/// names, so a shortcut written on another platform still reads back.
+const ALIASES: &[(&str, SerializableKeycode)] = &[
+    ("Control", SerializableKeycode::Ctrl),
+    ("⌃", SerializableKeycode::Ctrl),
+    ("⇧", SerializableKeycode::Shift),
+    ("⌥", SerializableKeycode::Alt),
+    ("Option", SerializableKeycode::Alt),
+    ("Opt", SerializableKeycode::Alt),
+    ("⌘", SerializableKeycode::Super),
+    ("Cmd", SerializableKeycode::Super),
+    ("Win", SerializableKeycode::Super),
+    ("Windows", SerializableKeycode::Super),
+    ("Meta", SerializableKeycode::Super),
+    ("LSuper", SerializableKeycode::LMeta),
+    ("RSuper", SerializableKeycode::RMeta),
+    ("LWin", SerializableKeycode::LMeta),
+    ("RWin", SerializableKeycode::RMeta),
+    ("LCmd", SerializableKeycode::Command),
+    ("RCmd", SerializableKeycode::RCommand),
+    ("Escape", SerializableKeycode::Escape),
+    ("Return", SerializableKeycode::Enter),
+    ("Del", SerializableKeycode::Delete),
+    ("Ins", SerializableKeycode::Insert),
+    ("PgUp", SerializableKeycode::PageUp),
+    ("PgDn", SerializableKeycode::PageDown),
+];
+
+impl SerializableKeycode {
+    /// Every key except [`SerializableKeycode::Unknown`].
+    pub const ALL: &[SerializableKeycode] = &[
+        SerializableKeycode::Key0,
+        SerializableKeycode::Key1,
+        SerializableKeycode::Key2,
+        SerializableKeycode::Key3,
+        SerializableKeycode::Key4,
+        SerializableKeycode::Key5,
+        SerializableKeycode::Key6,
+        SerializableKeycode::Key7,
+        SerializableKeycode::Key8,
+        SerializableKeycode::Key9,
+        SerializableKeycode::A,
+        SerializableKeycode::B,
+        SerializableKeycode::C,
+        SerializableKeycode::D,
+        SerializableKeycode::E,
+        SerializableKeycode::F,
+        SerializableKeycode::G,
+        SerializableKeycode::H,
+        SerializableKeycode::I,
+        SerializableKeycode::J,
+        SerializableKeycode::K,
+        SerializableKeycode::L,
+        SerializableKeycode::M,
+        SerializableKeycode::N,
+        SerializableKeycode::O,
+        SerializableKeycode::P,
+        SerializableKeycode::Q,
+        SerializableKeycode::R,
+        SerializableKeycode::S,
+        SerializableKeycode::T,
+        SerializableKeycode::U,
+        SerializableKeycode::V,
+        SerializableKeycode::W,
+        SerializableKeycode::X,
+        SerializableKeycode::Y,
+        SerializableKeycode::Z,
+        SerializableKeycode::F1,
+        SerializableKeycode::F2,
+        SerializableKeycode::F3,
+        SerializableKeycode::F4,
+        SerializableKeycode::F5,
+        SerializableKeycode::F6,
+        SerializableKeycode::F7,
+        SerializableKeycode::F8,
+        SerializableKeycode::F9,
+        SerializableKeycode::F10,
+        SerializableKeycode::F11,
+        SerializableKeycode::F12,
+        SerializableKeycode::F13,
+        SerializableKeycode::F14,
+        SerializableKeycode::F15,
+        SerializableKeycode::F16,
+        SerializableKeycode::F17,
+        SerializableKeycode::F18,
+        SerializableKeycode::F19,
+        SerializableKeycode::F20,
+        SerializableKeycode::F21,
+        SerializableKeycode::F22,
+        SerializableKeycode::F23,
+        SerializableKeycode::F24,
+        SerializableKeycode::Escape,
+        SerializableKeycode::Space,
+        SerializableKeycode::LControl,
+        SerializableKeycode::RControl,
+        SerializableKeycode::LShift,
+        SerializableKeycode::RShift,
+        SerializableKeycode::LAlt,
+        SerializableKeycode::RAlt,
+        SerializableKeycode::Command,
+        SerializableKeycode::RCommand,
+        SerializableKeycode::LOption,
+        SerializableKeycode::ROption,
+        SerializableKeycode::LMeta,
+        SerializableKeycode::RMeta,
+        SerializableKeycode::Ctrl,
+        SerializableKeycode::Shift,
+        SerializableKeycode::Alt,
+        SerializableKeycode::Super,
+        SerializableKeycode::Enter,
+        SerializableKeycode::Up,
+        SerializableKeycode::Down,
+        SerializableKeycode::Left,
+        SerializableKeycode::Right,
+        SerializableKeycode::Backspace,
+        SerializableKeycode::CapsLock,
+        SerializableKeycode::Tab,
+        SerializableKeycode::Home,
+        SerializableKeycode::End,
+        SerializableKeycode::PageUp,
+        SerializableKeycode::PageDown,
+        SerializableKeycode::Insert,
+        SerializableKeycode::Delete,
+        SerializableKeycode::Numpad0,
+        SerializableKeycode::Numpad1,
+        SerializableKeycode::Numpad2,
+        SerializableKeycode::Numpad3,
+        SerializableKeycode::Numpad4,
+        SerializableKeycode::Numpad5,
+        SerializableKeycode::Numpad6,
+        SerializableKeycode::Numpad7,
+        SerializableKeycode::Numpad8,
+        SerializableKeycode::Numpad9,
+        SerializableKeycode::NumpadSubtract,
+        SerializableKeycode::NumpadAdd,
+        SerializableKeycode::NumpadDivide,
+        SerializableKeycode::NumpadMultiply,
+        SerializableKeycode::NumpadEquals,
+        SerializableKeycode::NumpadEnter,
+        SerializableKeycode::NumpadDecimal,
+        SerializableKeycode::Grave,
+        SerializableKeycode::Minus,
+        SerializableKeycode::Equal,
+        SerializableKeycode::LeftBracket,
+        SerializableKeycode::RightBracket,
+        SerializableKeycode::BackSlash,
+        SerializableKeycode::Semicolon,
+        SerializableKeycode::Apostrophe,
+        SerializableKeycode::Comma,
+        SerializableKeycode::Dot,
+        SerializableKeycode::Slash,
+        SerializableKeycode::IntlBackslash,
+        SerializableKeycode::IntlRo,
+        SerializableKeycode::IntlYen,
+        SerializableKeycode::PrintScreen,
+        SerializableKeycode::ScrollLock,
+        SerializableKeycode::Pause,
+        SerializableKeycode::NumLock,
+        SerializableKeycode::ContextMenu,
+        SerializableKeycode::MediaPlayPause,
+        SerializableKeycode::MediaStop,
+        SerializableKeycode::MediaNext,
+        SerializableKeycode::MediaPrevious,
+        SerializableKeycode::VolumeUp,
+        SerializableKeycode::VolumeDown,
+        SerializableKeycode::VolumeMute,
+        SerializableKeycode::MouseMiddle,
+        SerializableKeycode::MouseBack,
+        SerializableKeycode::MouseForward,
+    ];
+
+    /// Short name shown to the user, e.g. `Ctrl` or `1`.
+    pub fn name(&self) -> &str {
+        match self {
+            SerializableKeycode::Key0 => "0",
+            SerializableKeycode::Key1 => "1",
+            SerializableKeycode::Key2 => "2",
+            SerializableKeycode::Key3 => "3",
+            SerializableKeycode::Key4 => "4",
+            SerializableKeycode::Key5 => "5",
+            SerializableKeycode::Key6 => "6",
+            SerializableKeycode::Key7 => "7",
+            SerializableKeycode::Key8 => "8",
+            SerializableKeycode::Key9 => "9",
+            SerializableKeycode::A => "A",
+            SerializableKeycode::B => "B",
+            SerializableKeycode::C => "C",
+            SerializableKeycode::D => "D",
+            SerializableKeycode::E => "E",
+            SerializableKeycode::F => "F",
+            SerializableKeycode::G => "G",
+            SerializableKeycode::H => "H",
+            SerializableKeycode::I => "I",
+            SerializableKeycode::J => "J",
+            SerializableKeycode::K => "K",
+            SerializableKeycode::L => "L",
+            SerializableKeycode::M => "M",
+            SerializableKeycode::N => "N",
+            SerializableKeycode::O => "O",
+            SerializableKeycode::P => "P",
+            SerializableKeycode::Q => "Q",
+            SerializableKeycode::R => "R",
+            SerializableKeycode::S => "S",
+            SerializableKeycode::T => "T",
+            SerializableKeycode::U => "U",
+            SerializableKeycode::V => "V",
+            SerializableKeycode::W => "W",
+            SerializableKeycode::X => "X",
+            SerializableKeycode::Y => "Y",
+            SerializableKeycode::Z => "Z",
+            SerializableKeycode::F1 => "F1",
+            SerializableKeycode::F2 => "F2",
+            SerializableKeycode::F3 => "F3",
+            SerializableKeycode::F4 => "F4",
+            SerializableKeycode::F5 => "F5",
+            SerializableKeycode::F6 => "F6",
+            SerializableKeycode::F7 => "F7",
+            SerializableKeycode::F8 => "F8",
+            SerializableKeycode::F9 => "F9",
+            SerializableKeycode::F10 => "F10",
+            SerializableKeycode::F11 => "F11",
+            SerializableKeycode::F12 => "F12",
+            SerializableKeycode::F13 => "F13",
+            SerializableKeycode::F14 => "F14",
+            SerializableKeycode::F15 => "F15",
+            SerializableKeycode::F16 => "F16",
+            SerializableKeycode::F17 => "F17",
+            SerializableKeycode::F18 => "F18",
+            SerializableKeycode::F19 => "F19",
+            SerializableKeycode::F20 => "F20",
+            SerializableKeycode::F21 => "F21",
+            SerializableKeycode::F22 => "F22",
+            SerializableKeycode::F23 => "F23",
+            SerializableKeycode::F24 => "F24",
+            SerializableKeycode::Escape => "Esc",
+            SerializableKeycode::Space => "Space",
+            SerializableKeycode::LControl => "LCtrl",
+            SerializableKeycode::RControl => "RCtrl",
+            SerializableKeycode::LShift => "LShift",
+            SerializableKeycode::RShift => "RShift",
+            SerializableKeycode::LAlt => "LAlt",
+            SerializableKeycode::RAlt => "RAlt",
+            SerializableKeycode::Command => "L⌘",
+            SerializableKeycode::RCommand => "R⌘",
+            SerializableKeycode::LOption => "L⌥",
+            SerializableKeycode::ROption => "R⌥",
+            SerializableKeycode::LMeta => LEFT_SUPER_NAME,
+            SerializableKeycode::RMeta => RIGHT_SUPER_NAME,
+            SerializableKeycode::Ctrl => "Ctrl",
+            SerializableKeycode::Shift => "Shift",
+            SerializableKeycode::Alt => ALT_NAME,
+            SerializableKeycode::Super => SUPER_NAME,
+            SerializableKeycode::Enter => "Enter",
+            SerializableKeycode::Up => "Up",
+            SerializableKeycode::Down => "Down",
+            SerializableKeycode::Left => "Left",
+            SerializableKeycode::Right => "Right",
+            SerializableKeycode::Backspace => "Backspace",
+            SerializableKeycode::CapsLock => "Caps Lock",
+            SerializableKeycode::Tab => "Tab",
+            SerializableKeycode::Home => "Home",
+            SerializableKeycode::End => "End",
+            SerializableKeycode::PageUp => "Page Up",
+            SerializableKeycode::PageDown => "Page Down",
+            SerializableKeycode::Insert => "Insert",
+            SerializableKeycode::Delete => "Delete",
+            SerializableKeycode::Numpad0 => "Num0",
+            SerializableKeycode::Numpad1 => "Num1",
+            SerializableKeycode::Numpad2 => "Num2",
+            SerializableKeycode::Numpad3 => "Num3",
+            SerializableKeycode::Numpad4 => "Num4",
+            SerializableKeycode::Numpad5 => "Num5",
+            SerializableKeycode::Numpad6 => "Num6",
+            SerializableKeycode::Numpad7 => "Num7",
+            SerializableKeycode::Numpad8 => "Num8",
+            SerializableKeycode::Numpad9 => "Num9",
+            SerializableKeycode::NumpadSubtract => "Num-",
+            SerializableKeycode::NumpadAdd => "NumPlus",
+            SerializableKeycode::NumpadDivide => "Num/",
+            SerializableKeycode::NumpadMultiply => "Num*",
+            SerializableKeycode::NumpadEquals => "Num=",
+            SerializableKeycode::NumpadEnter => "NumEnter",
+            SerializableKeycode::NumpadDecimal => "Num.",
+            SerializableKeycode::Grave => "`",
+            SerializableKeycode::Minus => "-",
+            SerializableKeycode::Equal => "=",
+            SerializableKeycode::LeftBracket => "[",
+            SerializableKeycode::RightBracket => "]",
+            SerializableKeycode::BackSlash => "\\",
+            SerializableKeycode::Semicolon => ";",
+            SerializableKeycode::Apostrophe => "'",
+            SerializableKeycode::Comma => "Comma",
+            SerializableKeycode::Dot => ".",
+            SerializableKeycode::Slash => "/",
+            SerializableKeycode::IntlBackslash => "ISO \\",
+            SerializableKeycode::IntlRo => "Ro",
+            SerializableKeycode::IntlYen => "Yen",
+            SerializableKeycode::PrintScreen => "Print Screen",
+            SerializableKeycode::ScrollLock => "Scroll Lock",
+            SerializableKeycode::Pause => "Pause",
+            SerializableKeycode::NumLock => "Num Lock",
+            SerializableKeycode::ContextMenu => "Menu",
+            SerializableKeycode::MediaPlayPause => "Play/Pause",
+            SerializableKeycode::MediaStop => "Media Stop",
+            SerializableKeycode::MediaNext => "Next Track",
+            SerializableKeycode::MediaPrevious => "Previous Track",
+            SerializableKeycode::VolumeUp => "Volume Up",
+            SerializableKeycode::VolumeDown => "Volume Down",
+            SerializableKeycode::VolumeMute => "Volume Mute",
+            SerializableKeycode::MouseMiddle => "Middle Mouse",
+            SerializableKeycode::MouseBack => "Mouse Back",
+            SerializableKeycode::MouseForward => "Mouse Forward",
+            SerializableKeycode::Unknown(name) => name,
+        }
+    }
+}
+
 impl fmt::Display for SerializableKeycode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            SerializableKeycode::Unknown(s) => write!(f, "Unknown({})", s),
-            _ => write!(f, "{:?}", self),
+            SerializableKeycode::Unknown(name) => write!(f, "<{name}>"),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+impl FromStr for SerializableKeycode {
+    type Err = String;
+
+    /// Reads back a key written by [`fmt::Display`], ignoring case.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        let unknown = text
+            .strip_prefix('<')
+            .and_then(|name| name.strip_suffix('>'))
+            .or_else(|| text.strip_prefix("Unknown(").and_then(|name| name.strip_suffix(')')));
+        if let Some(name) = unknown {
+            return Ok(SerializableKeycode::Unknown(name.to_string()));
+        }
+
+        // Anche i nomi delle varianti, come li stampava la versione precedente
+        SerializableKeycode::ALL
+            .iter()
+            .find(|key| key.name().eq_ignore_ascii_case(text) || format!("{key:?}").eq_ignore_ascii_case(text))
+            .or_else(|| {
+                ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias.eq_ignore_ascii_case(text))
+                    .map(|(_, key)| key)
+            })
+            .cloned()
+            .ok_or_else(|| format!("Unknown key \"{text}\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_key_reads_back_its_name() {
+        let unknown = SerializableKeycode::Unknown("IntlRo".to_string());
+
+        for key in SerializableKeycode::ALL.iter().chain([&unknown]) {
+            assert_eq!(key.to_string().parse::<SerializableKeycode>().as_ref(), Ok(key), "{key:?}");
         }
     }
-}
\ No newline at end of file
+
+    /// The same physical key seen by the browser, device_query and evdev.
+    #[cfg(target_os = "linux")]
+    const SAME_KEYS: &[(&str, Option<device_query::Keycode>, evdev::KeyCode)] = {
+        use device_query::Keycode as Dq;
+        use evdev::KeyCode as Ev;
+
+        &[
+            ("KeyA", Some(Dq::A), Ev::KEY_A),
+            ("KeyQ", Some(Dq::Q), Ev::KEY_Q),
+            ("KeyZ", Some(Dq::Z), Ev::KEY_Z),
+            ("Digit1", Some(Dq::Key1), Ev::KEY_1),
+            ("Digit0", Some(Dq::Key0), Ev::KEY_0),
+            ("Minus", Some(Dq::Minus), Ev::KEY_MINUS),
+            ("Equal", Some(Dq::Equal), Ev::KEY_EQUAL),
+            ("BracketLeft", Some(Dq::LeftBracket), Ev::KEY_LEFTBRACE),
+            ("BracketRight", Some(Dq::RightBracket), Ev::KEY_RIGHTBRACE),
+            ("Backslash", Some(Dq::BackSlash), Ev::KEY_BACKSLASH),
+            ("Semicolon", Some(Dq::Semicolon), Ev::KEY_SEMICOLON),
+            ("Quote", Some(Dq::Apostrophe), Ev::KEY_APOSTROPHE),
+            ("Backquote", Some(Dq::Grave), Ev::KEY_GRAVE),
+            ("Comma", Some(Dq::Comma), Ev::KEY_COMMA),
+            ("Period", Some(Dq::Dot), Ev::KEY_DOT),
+            ("Slash", Some(Dq::Slash), Ev::KEY_SLASH),
+            ("Escape", Some(Dq::Escape), Ev::KEY_ESC),
+            ("Space", Some(Dq::Space), Ev::KEY_SPACE),
+            ("Enter", Some(Dq::Enter), Ev::KEY_ENTER),
+            ("Tab", Some(Dq::Tab), Ev::KEY_TAB),
+            ("Backspace", Some(Dq::Backspace), Ev::KEY_BACKSPACE),
+            ("CapsLock", Some(Dq::CapsLock), Ev::KEY_CAPSLOCK),
+            ("ControlLeft", Some(Dq::LControl), Ev::KEY_LEFTCTRL),
+            ("ControlRight", Some(Dq::RControl), Ev::KEY_RIGHTCTRL),
+            ("ShiftLeft", Some(Dq::LShift), Ev::KEY_LEFTSHIFT),
+            ("ShiftRight", Some(Dq::RShift), Ev::KEY_RIGHTSHIFT),
+            ("AltLeft", Some(Dq::LAlt), Ev::KEY_LEFTALT),
+            ("AltRight", Some(Dq::RAlt), Ev::KEY_RIGHTALT),
+            ("MetaLeft", Some(Dq::LMeta), Ev::KEY_LEFTMETA),
+            ("MetaRight", Some(Dq::RMeta), Ev::KEY_RIGHTMETA),
+            ("F1", Some(Dq::F1), Ev::KEY_F1),
+            ("F12", Some(Dq::F12), Ev::KEY_F12),
+            ("F13", Some(Dq::F13), Ev::KEY_F13),
+            ("F24", None, Ev::KEY_F24),
+            ("ArrowUp", Some(Dq::Up), Ev::KEY_UP),
+            ("ArrowLeft", Some(Dq::Left), Ev::KEY_LEFT),
+            ("Home", Some(Dq::Home), Ev::KEY_HOME),
+            ("PageDown", Some(Dq::PageDown), Ev::KEY_PAGEDOWN),
+            ("Insert", Some(Dq::Insert), Ev::KEY_INSERT),
+            ("Delete", Some(Dq::Delete), Ev::KEY_DELETE),
+            ("Numpad0", Some(Dq::Numpad0), Ev::KEY_KP0),
+            ("Numpad1", Some(Dq::Numpad1), Ev::KEY_KP1),
+            ("NumpadAdd", Some(Dq::NumpadAdd), Ev::KEY_KPPLUS),
+            ("NumpadSubtract", Some(Dq::NumpadSubtract), Ev::KEY_KPMINUS),
+            ("NumpadMultiply", Some(Dq::NumpadMultiply), Ev::KEY_KPASTERISK),
+            ("NumpadDivide", Some(Dq::NumpadDivide), Ev::KEY_KPSLASH),
+            ("NumpadDecimal", Some(Dq::NumpadDecimal), Ev::KEY_KPDOT),
+            ("NumpadEnter", Some(Dq::NumpadEnter), Ev::KEY_KPENTER),
+            ("NumpadEqual", Some(Dq::NumpadEquals), Ev::KEY_KPEQUAL),
+            ("IntlBackslash", None, Ev::KEY_102ND),
+            ("IntlRo", None, Ev::KEY_RO),
+            ("IntlYen", None, Ev::KEY_YEN),
+            ("NumLock", None, Ev::KEY_NUMLOCK),
+            ("PrintScreen", None, Ev::KEY_SYSRQ),
+            ("ContextMenu", None, Ev::KEY_COMPOSE),
+            ("MediaPlayPause", None, Ev::KEY_PLAYPAUSE),
+            ("MediaTrackNext", None, Ev::KEY_NEXTSONG),
+            ("AudioVolumeUp", None, Ev::KEY_VOLUMEUP),
+            ("AudioVolumeMute", None, Ev::KEY_MUTE),
+        ]
+    };
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn every_backend_names_a_physical_key_alike() {
+        for (dom, device_query, evdev) in SAME_KEYS {
+            let key = SerializableKeycode::from_dom_code(dom);
+            assert!(!matches!(key, SerializableKeycode::Unknown(_)), "{dom} is unknown");
+            assert_eq!(SerializableKeycode::from(*evdev), key, "evdev {evdev:?}");
+            if let Some(device_query) = device_query {
+                assert_eq!(SerializableKeycode::from(*device_query), key, "device_query {device_query:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn debug_names_and_aliases_are_accepted() {
+        assert_eq!("LControl".parse(), Ok(SerializableKeycode::LControl));
+        assert_eq!("numpadadd".parse(), Ok(SerializableKeycode::NumpadAdd));
+        assert_eq!("Cmd".parse(), Ok(SerializableKeycode::Super));
+        assert_eq!("Unknown(IntlRo)".parse(), Ok(SerializableKeycode::Unknown("IntlRo".to_string())));
+        assert!("Nope".parse::<SerializableKeycode>().is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::shortcut::keycodes::SerializableKeycode;
+
+/// What the keys are labelled on the user's keyboard layout.
+///
+/// Shortcuts store the physical key, named after its place on a US QWERTY
+/// keyboard, so a board made on AZERTY fires on the same keys on QWERTZ.
+/// The labels come from the system layout where it can be read, see
+/// [`KeyboardLayout::detect`], and are learned from the key events egui sees,
+/// which carry both the physical key and the one the layout produces.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct KeyboardLayout {
+    /// Only the keys whose label differs from their US name.
+    labels: BTreeMap<String, String>,
+}
+
+impl KeyboardLayout {
+    /// Reads the labels of the typing keys from the system layout: on
+    /// Windows, X11 and in Chromium-based browsers, where the answer arrives
+    /// a few frames later through [`KeyboardLayout::learn`]. Elsewhere, e.g.
+    /// on macOS or Wayland without XWayland, they are only learned.
+    pub fn detect(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        web::request_labels();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        self.apply(system::labels());
+    }
+
+    /// Learns the labels of the keys pressed in this frame.
+    pub fn learn(&mut self, input: &egui::InputState) {
+        #[cfg(target_arch = "wasm32")]
+        if let Some(labels) = web::take_labels() {
+            self.apply(labels);
+        }
+
+        for event in &input.events {
+            let egui::Event::Key { key, physical_key: Some(physical_key), pressed: true, modifiers, .. } = event else {
+                continue;
+            };
+
+            // Con i modificatori il layout produce un altro simbolo
+            if !modifiers.is_none() {
+                continue;
+            }
+
+            let physical = SerializableKeycode::from(*physical_key);
+            if matches!(physical, SerializableKeycode::Unknown(_)) {
+                continue;
+            }
+
+            if key == physical_key {
+                self.labels.remove(physical.name());
+            } else {
+                self.labels.insert(physical.name().to_string(), key.symbol_or_name().to_string());
+            }
+        }
+    }
+
+    /// Stores the character each key types without modifiers.
+    fn apply(&mut self, labels: Vec<(SerializableKeycode, char)>) {
+        for (key, typed) in labels {
+            if us_character(&key) == Some(typed.to_ascii_lowercase()) {
+                self.labels.remove(key.name());
+            } else {
+                self.labels.insert(key.name().to_string(), typed.to_uppercase().to_string());
+            }
+        }
+    }
+
+    /// The label of `key` on this layout, its US name if not known.
+    pub fn label(&self, key: &SerializableKeycode) -> String {
+        self.labels
+            .get(key.name())
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Like [`crate::utils::shortcut_as_string`] with this layout's labels.
+    pub fn shortcut_label(&self, keys: &[SerializableKeycode]) -> String {
+        keys.iter()
+            .map(|key| self.label(key))
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    /// Like [`crate::utils::sequence_as_string`] with this layout's labels.
+    pub fn sequence_label(&self, steps: &[Vec<SerializableKeycode>]) -> String {
+        steps.iter()
+            .map(|step| self.shortcut_label(step))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// What the keys of the typing block type on a US layout, by scan code.
+const US_CHARACTERS: [(u32, &str); 4] = [
+    (0x02, "1234567890-="),
+    (0x10, "qwertyuiop[]"),
+    (0x1E, "asdfghjkl;'`"),
+    (0x2B, "\\zxcvbnm,./"),
+];
+
+fn us_character(key: &SerializableKeycode) -> Option<char> {
+    US_CHARACTERS.iter().find_map(|(first, row)| {
+        row.chars()
+            .zip(*first..)
+            .find(|(_, scan_code)| SerializableKeycode::from_scan_code(*scan_code).as_ref() == Some(key))
+            .map(|(character, _)| character)
+    })
+}
+
+/// The keys [`SerializableKeycode::from_scan_code`] knows, with their scan code.
+#[cfg(not(target_arch = "wasm32"))]
+fn typing_keys() -> impl Iterator<Item = (u32, SerializableKeycode)> {
+    (0..=0x58).filter_map(|scan_code| Some((scan_code, SerializableKeycode::from_scan_code(scan_code)?)))
+}
+
+#[cfg(target_os = "windows")]
+mod system {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{MapVirtualKeyW, MAPVK_VK_TO_CHAR, MAPVK_VSC_TO_VK};
+
+    use crate::shortcut::keycodes::SerializableKeycode;
+
+    pub fn labels() -> Vec<(SerializableKeycode, char)> {
+        super::typing_keys()
+            .filter_map(|(scan_code, key)| {
+                // SAFETY: MapVirtualKeyW only reads the active keyboard layout
+                // and accepts any code, returning 0 for the unknown ones.
+                let typed = unsafe {
+                    let virtual_key = MapVirtualKeyW(scan_code, MAPVK_VSC_TO_VK);
+                    MapVirtualKeyW(virtual_key, MAPVK_VK_TO_CHAR)
+                };
+
+                // Il bit alto segnala un tasto morto, come ^ sul layout francese
+                let typed = char::from_u32(typed & 0x7FFF_FFFF).filter(|typed| *typed != '\0')?;
+                Some((key, typed))
+            })
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod system {
+    use x11::xlib;
+
+    use crate::shortcut::keycodes::SerializableKeycode;
+
+    pub fn labels() -> Vec<(SerializableKeycode, char)> {
+        // SAFETY: the display is checked before use and closed before
+        // returning; XkbKeycodeToKeysym only reads its keymap.
+        unsafe {
+            let display = xlib::XOpenDisplay(std::ptr::null());
+            if display.is_null() {
+                return Vec::new();
+            }
+
+            let labels = super::typing_keys()
+                .filter_map(|(scan_code, key)| {
+                    // I keycode X sono quelli del kernel piu' 8
+                    let keysym = xlib::XkbKeycodeToKeysym(display, (scan_code + 8) as u8, 0, 0);
+                    Some((key, keysym_character(keysym)?))
+                })
+                .collect();
+
+            xlib::XCloseDisplay(display);
+            labels
+        }
+    }
+
+    /// The character of a keysym: Latin-1 ones are the character itself,
+    /// the others carry it with `0x0100_0000` added. Dead keys have none.
+    fn keysym_character(keysym: std::ffi::c_ulong) -> Option<char> {
+        match keysym {
+            0x20..=0x7E | 0xA0..=0xFF => char::from_u32(keysym as u32),
+            0x0100_0000..=0x0110_FFFF => char::from_u32((keysym - 0x0100_0000) as u32),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_arch = "wasm32")))]
+mod system {
+    use crate::shortcut::keycodes::SerializableKeycode;
+
+    pub fn labels() -> Vec<(SerializableKeycode, char)> {
+        Vec::new()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::cell::RefCell;
+
+    use wasm_bindgen::{JsCast, JsValue};
+
+    use crate::shortcut::keycodes::SerializableKeycode;
+
+    thread_local! {
+        static LABELS: RefCell<Option<Vec<(SerializableKeycode, char)>>> = const { RefCell::new(None) };
+    }
+
+    /// Asks `navigator.keyboard.getLayoutMap()`, which only Chromium has.
+    pub fn request_labels() {
+        wasm_bindgen_futures::spawn_local(async {
+            if let Ok(labels) = layout_map().await {
+                LABELS.with(|slot| *slot.borrow_mut() = Some(labels));
+            }
+        });
+    }
+
+    pub fn take_labels() -> Option<Vec<(SerializableKeycode, char)>> {
+        LABELS.with(|slot| slot.borrow_mut().take())
+    }
+
+    async fn layout_map() -> Result<Vec<(SerializableKeycode, char)>, JsValue> {
+        let navigator = web_sys::window().ok_or(JsValue::NULL)?.navigator();
+        let keyboard = js_sys::Reflect::get(&navigator, &"keyboard".into())?;
+        let get_layout_map: js_sys::Function = js_sys::Reflect::get(&keyboard, &"getLayoutMap".into())?.dyn_into()?;
+        let promise: js_sys::Promise = get_layout_map.call0(&keyboard)?.dyn_into()?;
+        let map: js_sys::Map = wasm_bindgen_futures::JsFuture::from(promise).await?.dyn_into()?;
+
+        let mut labels = Vec::new();
+        map.for_each(&mut |typed, code| {
+            let (Some(code), Some(typed)) = (code.as_string(), typed.as_string()) else {
+                return;
+            };
+            let key = SerializableKeycode::from_dom_code(&code);
+            if let (false, Some(typed)) = (matches!(key, SerializableKeycode::Unknown(_)), typed.chars().next()) {
+                labels.push((key, typed));
+            }
+        });
+        Ok(labels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn us_keys_keep_their_names() {
+        let mut layout = KeyboardLayout::default();
+        layout.apply(vec![(SerializableKeycode::A, 'a'), (SerializableKeycode::Comma, ',')]);
+
+        assert_eq!(layout, KeyboardLayout::default());
+        assert_eq!(layout.label(&SerializableKeycode::Comma), "Comma");
+    }
+
+    #[test]
+    fn other_layouts_relabel_the_keys() {
+        // AZERTY: A e Q scambiati, M al posto del punto e virgola
+        let mut layout = KeyboardLayout::default();
+        layout.apply(vec![
+            (SerializableKeycode::Q, 'a'),
+            (SerializableKeycode::A, 'q'),
+            (SerializableKeycode::Semicolon, 'm'),
+            (SerializableKeycode::Key2, 'é'),
+        ]);
+
+        assert_eq!(layout.shortcut_label(&[SerializableKeycode::Ctrl, SerializableKeycode::Q]), "Ctrl+A");
+        assert_eq!(layout.label(&SerializableKeycode::Semicolon), "M");
+        assert_eq!(layout.label(&SerializableKeycode::Key2), "É");
+    }
+}
@@ -1,11 +1,54 @@
 pub mod interface;
 
+/// Where the desktop reads the global shortcuts from.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShortcutBackendKind {
+    /// device_query: X11, Windows and macOS.
+    #[default]
+    DeviceQuery,
+    /// The Linux input devices, which also work under Wayland. Needs read
+    /// access to `/dev/input`.
+    Evdev,
+}
+
+impl ShortcutBackendKind {
+    /// Whether the backend can read the keys on this machine, and why not.
+    pub fn check(self) -> Result<(), String> {
+        match self {
+            ShortcutBackendKind::DeviceQuery => Ok(()),
+            #[cfg(target_os = "linux")]
+            ShortcutBackendKind::Evdev => evdev::EvdevKeyboard::open().map(|_| ()),
+            #[cfg(not(target_os = "linux"))]
+            ShortcutBackendKind::Evdev => Err("evdev is only available on Linux".to_string()),
+        }
+    }
+}
+
 pub mod keycodes;
 
+pub mod layout;
+
+pub mod chord;
+
+pub mod conflict;
+
+pub mod sequence;
+
+pub mod gesture;
+
+pub mod monitor;
+
+pub mod action;
+
+pub mod trigger;
+
 #[cfg(not(target_arch = "wasm32"))]
 pub mod desktop;
 #[cfg(not(target_arch = "wasm32"))]
 pub use desktop::DesktopShortcutListener as PlatformShortcutListener;
+#[cfg(target_os = "linux")]
+pub mod evdev;
 
 #[cfg(target_arch = "wasm32")]
 pub mod web;
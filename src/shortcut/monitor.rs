@@ -0,0 +1,28 @@
+use std::sync::{Arc, Mutex};
+
+/// Last input received from a trigger source, read by the learn widgets.
+pub struct InputMonitor<T>(Arc<Mutex<Option<T>>>);
+
+impl<T> InputMonitor<T> {
+    pub fn record(&self, input: T) {
+        if let Ok(mut last) = self.0.lock() {
+            *last = Some(input);
+        }
+    }
+
+    pub fn take(&self) -> Option<T> {
+        self.0.lock().ok().and_then(|mut last| last.take())
+    }
+}
+
+impl<T> Clone for InputMonitor<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Default for InputMonitor<T> {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(None)))
+    }
+}
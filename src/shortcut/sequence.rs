@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+// Su wasm `std::time::Instant` va in panic; sul desktop e' lo stesso tipo
+use web_time::Instant;
+
+use crate::shortcut::chord::most_specific;
+use crate::shortcut::keycodes::SerializableKeycode;
+use crate::shortcut::trigger::Binding;
+
+/// Follows the steps of multi-chord bindings (`F13` then `3`) across polls.
+///
+/// A binding that is also the prefix of a longer one is held back until the
+/// sequence times out or is broken by another key, so both stay reachable.
+#[derive(Default)]
+pub struct SequenceMatcher {
+    matched: Vec<Vec<SerializableKeycode>>,
+    last_step: Option<Instant>,
+    pending: Option<Vec<Vec<SerializableKeycode>>>,
+}
+
+impl SequenceMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` while part of a sequence has been typed.
+    pub fn in_progress(&self) -> bool {
+        !self.matched.is_empty()
+    }
+
+    /// Feeds one poll of the keyboard and returns the binding to fire, if any.
+    ///
+    /// `just_pressed` tells whether a chord went down in this poll and
+    /// `key_pressed` whether any non-modifier key did.
+    pub fn poll<'a>(
+        &mut self,
+        bindings: &'a [Binding],
+        now: Instant,
+        timeout: Duration,
+        just_pressed: impl Fn(&[SerializableKeycode]) -> bool,
+        key_pressed: bool,
+    ) -> Option<&'a Binding> {
+        if self.last_step.is_some_and(|last| now.duration_since(last) > timeout) {
+            let fired = self.take_pending(bindings);
+            self.reset();
+            if fired.is_some() {
+                return fired;
+            }
+        }
+
+        let depth = self.matched.len();
+        let candidates = bindings.iter().filter(|binding| {
+            binding.steps.len() > depth
+                && binding.steps.starts_with(&self.matched)
+                && just_pressed(&binding.steps[depth])
+        });
+
+        let Some(binding) = most_specific(candidates, |binding| &binding.steps[depth]) else {
+            if key_pressed && self.in_progress() {
+                // Tasto estraneo: la sequenza e' interrotta, si riparte da capo
+                let fired = self.take_pending(bindings);
+                self.reset();
+                return fired.or_else(|| self.poll(bindings, now, timeout, just_pressed, key_pressed));
+            }
+            return None;
+        };
+
+        self.matched.push(binding.steps[depth].clone());
+        self.last_step = Some(now);
+
+        let extended = bindings
+            .iter()
+            .any(|other| other.steps.len() > self.matched.len() && other.steps.starts_with(&self.matched));
+
+        if !extended {
+            self.reset();
+            return Some(binding);
+        }
+
+        self.pending = (binding.steps.len() == self.matched.len()).then(|| binding.steps.clone());
+        None
+    }
+
+    fn take_pending<'a>(&mut self, bindings: &'a [Binding]) -> Option<&'a Binding> {
+        let pending = self.pending.take()?;
+        bindings.iter().find(|binding| binding.steps == pending)
+    }
+
+    fn reset(&mut self) {
+        self.matched.clear();
+        self.last_step = None;
+        self.pending = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shortcut::trigger::TriggerAction;
+    use crate::sound::TriggerMode;
+
+    use super::*;
+    use SerializableKeycode::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(800);
+
+    fn binding(steps: &[&[SerializableKeycode]]) -> Binding {
+        Binding {
+            steps: steps.iter().map(|step| step.to_vec()).collect(),
+            action: TriggerAction::ToggleRecording,
+            mode: TriggerMode::Press,
+        }
+    }
+
+    /// Feeds a poll in which `keys` went down, `at` milliseconds in.
+    fn press<'a>(
+        matcher: &mut SequenceMatcher,
+        bindings: &'a [Binding],
+        start: Instant,
+        at: u64,
+        keys: &[SerializableKeycode],
+    ) -> Option<&'a Binding> {
+        let now = start + Duration::from_millis(at);
+        matcher.poll(bindings, now, TIMEOUT, |chord| chord == keys, !keys.is_empty())
+    }
+
+    #[test]
+    fn completes_a_sequence() {
+        let bindings = [binding(&[&[F13], &[Key3]])];
+        let mut matcher = SequenceMatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(press(&mut matcher, &bindings, start, 0, &[F13]), None);
+        assert!(matcher.in_progress());
+        assert_eq!(press(&mut matcher, &bindings, start, 100, &[Key3]), Some(&bindings[0]));
+        assert!(!matcher.in_progress());
+    }
+
+    #[test]
+    fn a_late_step_starts_over() {
+        let bindings = [binding(&[&[F13], &[Key3]])];
+        let mut matcher = SequenceMatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(press(&mut matcher, &bindings, start, 0, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 900, &[Key3]), None);
+        assert!(!matcher.in_progress());
+
+        // Al limite del timeout il passo vale ancora
+        assert_eq!(press(&mut matcher, &bindings, start, 1000, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 1800, &[Key3]), Some(&bindings[0]));
+    }
+
+    #[test]
+    fn a_prefix_binding_fires_when_the_sequence_times_out() {
+        let bindings = [binding(&[&[F13]]), binding(&[&[F13], &[Key3]])];
+        let mut matcher = SequenceMatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(press(&mut matcher, &bindings, start, 0, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 500, &[]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 900, &[]), Some(&bindings[0]));
+        assert!(!matcher.in_progress());
+    }
+
+    #[test]
+    fn a_prefix_binding_fires_when_another_key_breaks_the_sequence() {
+        let bindings = [binding(&[&[F13]]), binding(&[&[F13], &[Key3]])];
+        let mut matcher = SequenceMatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(press(&mut matcher, &bindings, start, 0, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 100, &[Key4]), Some(&bindings[0]));
+        assert!(!matcher.in_progress());
+    }
+
+    #[test]
+    fn a_wrong_step_restarts_from_a_matching_prefix() {
+        let bindings = [binding(&[&[F13], &[Key1]]), binding(&[&[F14], &[Key2]])];
+        let mut matcher = SequenceMatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(press(&mut matcher, &bindings, start, 0, &[F13]), None);
+        // F14 non continua la prima sequenza ma ne apre un'altra
+        assert_eq!(press(&mut matcher, &bindings, start, 100, &[F14]), None);
+        assert!(matcher.in_progress());
+        assert_eq!(press(&mut matcher, &bindings, start, 200, &[Key1]), None);
+        assert!(!matcher.in_progress());
+
+        assert_eq!(press(&mut matcher, &bindings, start, 300, &[F14]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 400, &[Key2]), Some(&bindings[1]));
+    }
+
+    #[test]
+    fn overlapping_sequences_follow_the_typed_steps() {
+        let bindings = [
+            binding(&[&[F13], &[Key1]]),
+            binding(&[&[F13], &[Key2]]),
+            binding(&[&[F13], &[Key1], &[Key2]]),
+        ];
+        let mut matcher = SequenceMatcher::new();
+        let start = Instant::now();
+
+        assert_eq!(press(&mut matcher, &bindings, start, 0, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 100, &[Key2]), Some(&bindings[1]));
+
+        // F13 1 e' anche l'inizio di F13 1 2: si aspetta il passo successivo
+        assert_eq!(press(&mut matcher, &bindings, start, 200, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 300, &[Key1]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 400, &[Key2]), Some(&bindings[2]));
+
+        assert_eq!(press(&mut matcher, &bindings, start, 500, &[F13]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 600, &[Key1]), None);
+        assert_eq!(press(&mut matcher, &bindings, start, 1500, &[]), Some(&bindings[0]));
+    }
+}
@@ -0,0 +1,957 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+
+// Su wasm `std::time::Instant` va in panic; sul desktop e' lo stesso tipo
+use web_time::Instant;
+
+use crate::Sound;
+use crate::audio::RecordingTarget;
+use crate::audio::interface::AudioBackend;
+use crate::gamepad::GamepadTrigger;
+use crate::midi::{MidiMessage, MidiTrigger};
+use crate::midi::feedback::FeedbackProfile;
+use crate::shortcut::ShortcutBackendKind;
+use crate::shortcut::action::{AppAction, VOLUME_STEP};
+use crate::shortcut::gesture::{GestureTiming, GestureTracker};
+use crate::shortcut::interface::ShortcutListener;
+use crate::shortcut::keycodes::SerializableKeycode;
+use crate::shortcut::sequence::SequenceMatcher;
+use crate::sound::TriggerMode;
+
+/// What happens when the shortcut of a [`Binding`] is pressed.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TriggerAction {
+    /// The sound is shared by its bindings and has no file contents.
+    PlaySound(Arc<Sound>),
+    ToggleRecording,
+    App(AppAction),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Binding {
+    /// Chords to press one after the other; usually just one.
+    pub steps: Vec<Vec<SerializableKeycode>>,
+    pub action: TriggerAction,
+    pub mode: TriggerMode,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MidiBinding {
+    pub trigger: MidiTrigger,
+    pub velocity_to_volume: bool,
+    pub action: TriggerAction,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GamepadBinding {
+    pub trigger: GamepadTrigger,
+    pub action: TriggerAction,
+}
+
+/// What fires while a board is active.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BoardBindings {
+    /// Bindings fired on press, or held in [`TriggerMode::Gate`], the app
+    /// hotkeys included.
+    pub bindings: Vec<Binding>,
+    /// Single-chord bindings fired by a tap, double tap or long press.
+    pub gesture_bindings: Vec<Binding>,
+    pub midi_bindings: Vec<MidiBinding>,
+    pub gamepad_bindings: Vec<GamepadBinding>,
+    /// The sounds bound to a MIDI pad, the ones being edited too: the trigger
+    /// loop lights their pads.
+    pub pads: Vec<Arc<Sound>>,
+    /// Overrides [`TriggerSnapshot::stop_on_new`] on this board.
+    pub stop_on_new: Option<bool>,
+}
+
+const NO_BINDINGS: &BoardBindings = &BoardBindings {
+    bindings: Vec::new(),
+    gesture_bindings: Vec::new(),
+    midi_bindings: Vec::new(),
+    gamepad_bindings: Vec::new(),
+    pads: Vec::new(),
+    stop_on_new: None,
+};
+
+/// Everything the trigger loop needs to react to a shortcut. The UI publishes
+/// a new snapshot when something changes, so the loop never has to lock the
+/// sounds or the settings.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TriggerSnapshot {
+    /// Every board, so the hotkeys can switch board without the UI, e.g.
+    /// while the window is minimized.
+    pub boards: Vec<BoardBindings>,
+    pub stop_on_new: bool,
+    pub recording: RecordingTarget,
+    /// Longest pause allowed between the steps of a sequence.
+    pub sequence_timeout: Duration,
+    pub gesture_timing: GestureTiming,
+    pub shortcut_backend: ShortcutBackendKind,
+    /// Set in focus-only mode while the window is in the background: the
+    /// keyboard shortcuts are ignored, MIDI and gamepads keep working.
+    pub keyboard_paused: bool,
+    /// What the controller pads show for each state.
+    pub feedback_profile: FeedbackProfile,
+}
+
+impl TriggerSnapshot {
+    /// The bindings of board `idx`, none if it does not exist (yet).
+    pub fn board(&self, idx: usize) -> &BoardBindings {
+        self.boards.get(idx).unwrap_or(NO_BINDINGS)
+    }
+}
+
+/// The snapshot shared with the trigger loop, plus whether the hotkeys are
+/// armed and which board is active: the loop changes those itself, so they
+/// cannot be part of the snapshot published by the UI.
+#[derive(Clone)]
+pub struct SharedSnapshot {
+    current: Arc<RwLock<Arc<TriggerSnapshot>>>,
+    armed: Arc<AtomicBool>,
+    active_board: Arc<AtomicUsize>,
+    repaint: Arc<Mutex<Option<egui::Context>>>,
+}
+
+impl Default for SharedSnapshot {
+    fn default() -> Self {
+        Self {
+            current: Arc::default(),
+            armed: Arc::new(AtomicBool::new(true)),
+            active_board: Arc::default(),
+            repaint: Arc::default(),
+        }
+    }
+}
+
+impl SharedSnapshot {
+    pub fn load(&self) -> Arc<TriggerSnapshot> {
+        self.current
+            .read()
+            .map(|snapshot| Arc::clone(&snapshot))
+            .unwrap_or_default()
+    }
+
+    /// Replaces the current snapshot, unless nothing changed.
+    pub fn publish(&self, snapshot: TriggerSnapshot) {
+        if *self.load() == snapshot {
+            return;
+        }
+
+        if let Ok(mut current) = self.current.write() {
+            *current = Arc::new(snapshot);
+        }
+    }
+
+    /// When disarmed only the [`AppAction::ToggleHotkeys`] hotkey works.
+    pub fn armed(&self) -> bool {
+        self.armed.load(Ordering::Relaxed)
+    }
+
+    pub fn set_armed(&self, armed: bool) {
+        self.armed.store(armed, Ordering::Relaxed);
+        self.request_repaint();
+    }
+
+    /// The board whose bindings fire; the UI shows the same one.
+    pub fn active_board(&self) -> usize {
+        self.active_board.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active_board(&self, idx: usize) {
+        self.active_board.store(idx, Ordering::Relaxed);
+        self.request_repaint();
+    }
+
+    /// Moves `step` boards forward or back among `count`, wrapping around.
+    pub fn step_board(&self, step: isize, count: usize) {
+        if count == 0 {
+            return;
+        }
+
+        let target = (self.active_board() as isize + step).rem_euclid(count as isize);
+        self.set_active_board(target as usize);
+    }
+
+    // Il cambio puo' arrivare dal thread dei trigger: la UI deve aggiornarsi
+    fn request_repaint(&self) {
+        if let Ok(repaint) = self.repaint.lock() && let Some(ctx) = repaint.as_ref() {
+            ctx.request_repaint();
+        }
+    }
+
+    /// Repaints the UI when the hotkeys are armed or disarmed, or the board
+    /// changes.
+    pub fn set_repaint_context(&self, ctx: egui::Context) {
+        if let Ok(mut repaint) = self.repaint.lock() {
+            *repaint = Some(ctx);
+        }
+    }
+}
+
+/// What the triggered actions drive besides playback: the desktop audio
+/// thread or the browser audio.
+pub trait TriggerTarget: AudioBackend {
+    fn is_recording(&self) -> bool;
+    fn start_recording(&mut self, target: RecordingTarget);
+    fn stop_recording(&mut self);
+    fn toggle_mute(&mut self);
+    fn nudge_volume(&mut self, step: f32);
+}
+
+/// Runs a triggered action, `gain` scaling the sound it plays.
+pub fn fire(
+    shared: &SharedSnapshot,
+    snapshot: &TriggerSnapshot,
+    action: &TriggerAction,
+    gain: f32,
+    audio: &mut impl TriggerTarget,
+) {
+    if !shared.armed() && *action != TriggerAction::App(AppAction::ToggleHotkeys) {
+        return;
+    }
+
+    let sound = match action {
+        TriggerAction::App(action) => {
+            match action {
+                AppAction::StopAll => audio.stop_all(),
+                AppAction::ToggleMute => audio.toggle_mute(),
+                AppAction::VolumeUp => audio.nudge_volume(VOLUME_STEP),
+                AppAction::VolumeDown => audio.nudge_volume(-VOLUME_STEP),
+                AppAction::ToggleHotkeys => shared.set_armed(!shared.armed()),
+                AppAction::NextBoard => shared.step_board(1, snapshot.boards.len()),
+                AppAction::PreviousBoard => shared.step_board(-1, snapshot.boards.len()),
+            }
+            return;
+        }
+        TriggerAction::ToggleRecording => {
+            // Avvia o ferma la registrazione della sessione
+            if audio.is_recording() {
+                audio.stop_recording();
+            } else {
+                audio.start_recording(snapshot.recording.clone());
+            }
+            return;
+        }
+        TriggerAction::PlaySound(sound) => sound.as_ref(),
+    };
+
+    let board = snapshot.board(shared.active_board());
+    let stop_on_new = board.stop_on_new.unwrap_or(snapshot.stop_on_new);
+    if let Err(error) = audio.trigger(sound, gain, stop_on_new) {
+        eprintln!("{error}");
+    }
+}
+
+/// Fires the binding of a MIDI message, if any; the velocity scales the
+/// volume when the binding asks for it.
+pub fn fire_midi(
+    shared: &SharedSnapshot,
+    snapshot: &TriggerSnapshot,
+    message: MidiMessage,
+    audio: &mut impl TriggerTarget,
+) {
+    let binding = snapshot
+        .board(shared.active_board())
+        .midi_bindings
+        .iter()
+        .find(|binding| binding.trigger == message.trigger);
+
+    if let Some(binding) = binding {
+        let gain = if binding.velocity_to_volume { message.gain() } else { 1.0 };
+        fire(shared, snapshot, &binding.action, gain, audio);
+    }
+}
+
+/// Fires the binding of a gamepad trigger, if any.
+pub fn fire_gamepad(
+    shared: &SharedSnapshot,
+    snapshot: &TriggerSnapshot,
+    trigger: GamepadTrigger,
+    audio: &mut impl TriggerTarget,
+) {
+    let binding = snapshot
+        .board(shared.active_board())
+        .gamepad_bindings
+        .iter()
+        .find(|binding| binding.trigger == trigger);
+
+    if let Some(binding) = binding {
+        fire(shared, snapshot, &binding.action, 1.0, audio);
+    }
+}
+
+/// Turns the keys held on a [`ShortcutListener`] into triggered actions:
+/// sequences, gestures, and the sounds to stop when a held chord is released.
+#[derive(Default)]
+pub struct KeyboardTriggers {
+    sequences: SequenceMatcher,
+    gestures: GestureTracker,
+    /// Gated sounds still playing, with the chord keeping them alive.
+    gated: Vec<(Vec<SerializableKeycode>, Arc<Sound>)>,
+}
+
+impl KeyboardTriggers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `true` while a sequence or a gesture waits for a timeout.
+    pub fn in_progress(&self) -> bool {
+        self.sequences.in_progress() || self.gestures.in_progress()
+    }
+
+    /// Checks `keyboard`, already updated, and fires what it triggers.
+    pub fn poll(
+        &mut self,
+        keyboard: &dyn ShortcutListener,
+        shared: &SharedSnapshot,
+        snapshot: &TriggerSnapshot,
+        audio: &mut impl TriggerTarget,
+    ) {
+        let now = Instant::now();
+
+        // Un suono in modalita' gate dura quanto l'accordo che l'ha avviato
+        self.gated.retain(|(chord, sound)| {
+            let held = keyboard.is_pressed(chord);
+            if !held {
+                audio.stop(sound);
+            }
+            held
+        });
+
+        if snapshot.keyboard_paused {
+            // Si riparte da zero al ritorno del focus
+            self.sequences = SequenceMatcher::new();
+            self.gestures = GestureTracker::new();
+            return;
+        }
+
+        let board = snapshot.board(shared.active_board());
+
+        // Solo sul fronte di pressione: una shortcut tenuta premuta non si ripete.
+        // Se piu' accordi scattano insieme vince quello piu' specifico.
+        let fired = self.sequences.poll(
+            &board.bindings,
+            now,
+            snapshot.sequence_timeout,
+            |chord| keyboard.just_pressed(chord),
+            keyboard.any_key_just_pressed(),
+        );
+
+        if let Some(binding) = fired {
+            fire(shared, snapshot, &binding.action, 1.0, audio);
+
+            if binding.mode == TriggerMode::Gate
+                && shared.armed()
+                && let (Some(chord), TriggerAction::PlaySound(sound)) = (binding.steps.last(), &binding.action)
+            {
+                self.gated.push((chord.clone(), Arc::clone(sound)));
+            }
+        }
+
+        let gestures = self.gestures.poll(
+            &board.gesture_bindings,
+            now,
+            snapshot.gesture_timing,
+            |chord| keyboard.is_pressed(chord),
+        );
+
+        for binding in gestures {
+            fire(shared, snapshot, &binding.action, 1.0, audio);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use desktop::{spawn_trigger_loop, TriggerInputs};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod desktop {
+    use std::collections::HashMap;
+    use std::sync::mpsc::Receiver;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::audio::desktop::DesktopAudioRemote;
+    use crate::audio::interface::PlaybackState;
+    use crate::gamepad::GamepadTrigger;
+    use crate::midi::feedback::{pad_values, FileCache};
+    use crate::midi::{MidiMessage, MidiTrigger, PlatformMidiOutput};
+    use crate::shortcut::desktop::listener;
+
+    use super::{fire_gamepad, fire_midi, KeyboardTriggers, RecordingTarget, SharedSnapshot, TriggerSnapshot, TriggerTarget};
+
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+    /// Event streams from the trigger sources other than the keyboard.
+    #[derive(Default)]
+    pub struct TriggerInputs {
+        pub midi: Option<Receiver<MidiMessage>>,
+        pub gamepad: Option<Receiver<GamepadTrigger>>,
+    }
+
+    /// Polls the global shortcuts and the other trigger sources on a
+    /// dedicated thread and forwards the triggered actions to the audio thread.
+    /// The pads on `pads` follow the playback from the same thread, so they
+    /// stay current while the window is minimized.
+    pub fn spawn_trigger_loop(
+        shared: SharedSnapshot,
+        mut audio: DesktopAudioRemote,
+        inputs: TriggerInputs,
+        pads: Arc<Mutex<PlatformMidiOutput>>,
+    ) {
+        thread::spawn(move || {
+            let mut backend = shared.load().shortcut_backend;
+            let mut keyboard = listener(backend);
+            let mut triggers = KeyboardTriggers::new();
+            let mut files = FileCache::new();
+
+            loop {
+                let snapshot = shared.load();
+                if snapshot.shortcut_backend != backend {
+                    backend = snapshot.shortcut_backend;
+                    keyboard = listener(backend);
+                    triggers = KeyboardTriggers::new();
+                }
+                keyboard.update();
+                triggers.poll(keyboard.as_ref(), &shared, &snapshot, &mut audio);
+
+                for message in inputs.midi.iter().flat_map(|events| events.try_iter()) {
+                    fire_midi(&shared, &snapshot, message, &mut audio);
+                }
+
+                for trigger in inputs.gamepad.iter().flat_map(|events| events.try_iter()) {
+                    fire_gamepad(&shared, &snapshot, trigger, &mut audio);
+                }
+
+                let values = pad_feedback(&shared, &snapshot, &audio.state(), &mut files);
+                if let Ok(mut output) = pads.lock() {
+                    output.update(&values);
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+    }
+
+    /// The value each pad of the active board should show, even when the
+    /// board was switched by a hotkey and the UI has not caught up.
+    pub(super) fn pad_feedback(
+        shared: &SharedSnapshot,
+        snapshot: &TriggerSnapshot,
+        playback: &PlaybackState,
+        files: &mut FileCache,
+    ) -> HashMap<MidiTrigger, u8> {
+        let board = snapshot.board(shared.active_board());
+        pad_values(&board.pads, playback, &snapshot.feedback_profile, files)
+    }
+
+    impl TriggerTarget for DesktopAudioRemote {
+        fn is_recording(&self) -> bool {
+            DesktopAudioRemote::is_recording(self)
+        }
+
+        fn start_recording(&mut self, target: RecordingTarget) {
+            DesktopAudioRemote::start_recording(self, target);
+        }
+
+        fn stop_recording(&mut self) {
+            DesktopAudioRemote::stop_recording(self);
+        }
+
+        fn toggle_mute(&mut self) {
+            DesktopAudioRemote::toggle_mute(self);
+        }
+
+        fn nudge_volume(&mut self, step: f32) {
+            DesktopAudioRemote::nudge_volume(self, step);
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub use web::WebTriggers;
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use std::time::Duration;
+
+    use crate::audio::web::WebAudio;
+    use crate::shortcut::interface::ShortcutListener;
+    use crate::shortcut::web::WebShortcutListener;
+
+    use super::{KeyboardTriggers, RecordingTarget, SharedSnapshot, TriggerTarget};
+
+    /// The browser has no threads to poll from: the UI checks the shortcuts
+    /// every frame and the listener asks for a frame when a key changes.
+    pub struct WebTriggers {
+        listener: WebShortcutListener,
+        triggers: KeyboardTriggers,
+    }
+
+    impl WebTriggers {
+        pub fn new(ctx: egui::Context) -> Self {
+            let mut listener = WebShortcutListener::new();
+            listener.set_repaint_context(ctx);
+
+            Self {
+                listener,
+                triggers: KeyboardTriggers::new(),
+            }
+        }
+
+        pub fn poll(&mut self, shared: &SharedSnapshot, audio: &mut WebAudio, ctx: &egui::Context) {
+            self.listener.update();
+            let snapshot = shared.load();
+            self.triggers.poll(&self.listener, shared, &snapshot, audio);
+
+            // Sequenze e gesti in corso devono poter scadere anche senza altri tasti
+            if self.triggers.in_progress() {
+                ctx.request_repaint_after(Duration::from_millis(20));
+            }
+        }
+    }
+
+    impl TriggerTarget for WebAudio {
+        fn is_recording(&self) -> bool {
+            WebAudio::is_recording(self)
+        }
+
+        fn start_recording(&mut self, target: RecordingTarget) {
+            WebAudio::start_recording(self, target);
+        }
+
+        fn stop_recording(&mut self) {
+            WebAudio::stop_recording(self);
+        }
+
+        fn toggle_mute(&mut self) {
+            WebAudio::toggle_mute(self);
+        }
+
+        fn nudge_volume(&mut self, step: f32) {
+            WebAudio::nudge_volume(self, step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+
+    use crate::audio::interface::PlaybackState;
+    use crate::audio::null::NullAudio;
+    use crate::audio::synth::Generator;
+    use crate::midi::feedback::FileCache;
+    use crate::sound::RetriggerPolicy;
+
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(10);
+
+    fn synth(generator: Generator, retrigger: RetriggerPolicy) -> Arc<Sound> {
+        Arc::new(Sound {
+            generator: Some(generator),
+            retrigger,
+            editing: false,
+            ..Sound::default()
+        })
+    }
+
+    /// Presses the shortcut of `sound`, then lets some time pass.
+    fn press(shared: &SharedSnapshot, snapshot: &TriggerSnapshot, sound: &Arc<Sound>, audio: &mut NullAudio) {
+        fire(shared, snapshot, &TriggerAction::PlaySound(Arc::clone(sound)), 1.0, audio);
+        audio.advance(STEP);
+    }
+
+    /// When each sound started and stopped, in start order.
+    fn timeline(audio: &NullAudio) -> Vec<(Duration, Option<Duration>)> {
+        audio.history().iter().map(|record| (record.started_at, record.stopped_at)).collect()
+    }
+
+    /// [`NullAudio`] plus the state of the controls it does not have.
+    #[derive(Default)]
+    struct Headless {
+        audio: NullAudio,
+        recording: Option<RecordingTarget>,
+        muted: bool,
+        volume_steps: Vec<f32>,
+    }
+
+    impl AudioBackend for Headless {
+        fn play_with_gain(&mut self, sound: &Sound, gain: f32) -> Result<(), String> {
+            self.audio.play_with_gain(sound, gain)
+        }
+
+        fn stop(&mut self, sound: &Sound) {
+            self.audio.stop(sound);
+        }
+
+        fn stop_all(&mut self) {
+            self.audio.stop_all();
+        }
+
+        fn is_playing(&self, sound: Option<&Sound>) -> bool {
+            self.audio.is_playing(sound)
+        }
+    }
+
+    impl TriggerTarget for Headless {
+        fn is_recording(&self) -> bool {
+            self.recording.is_some()
+        }
+
+        fn start_recording(&mut self, target: RecordingTarget) {
+            self.recording = Some(target);
+        }
+
+        fn stop_recording(&mut self) {
+            self.recording = None;
+        }
+
+        fn toggle_mute(&mut self) {
+            self.muted = !self.muted;
+        }
+
+        fn nudge_volume(&mut self, step: f32) {
+            self.volume_steps.push(step);
+        }
+    }
+
+    fn app(action: AppAction) -> TriggerAction {
+        TriggerAction::App(action)
+    }
+
+    /// A keyboard whose held keys are set by the test, one poll at a time.
+    #[derive(Default)]
+    struct ScriptedKeyboard {
+        held: Vec<SerializableKeycode>,
+        before: Vec<SerializableKeycode>,
+    }
+
+    impl ScriptedKeyboard {
+        fn hold(&mut self, keys: &[SerializableKeycode]) {
+            self.before = std::mem::replace(&mut self.held, keys.to_vec());
+        }
+    }
+
+    impl ShortcutListener for ScriptedKeyboard {
+        fn update(&mut self) {}
+
+        fn is_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+            shortcut.iter().all(|key| self.held.contains(key))
+        }
+
+        fn just_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+            self.is_pressed(shortcut) && !shortcut.iter().all(|key| self.before.contains(key))
+        }
+
+        fn any_key_just_pressed(&self) -> bool {
+            self.held.iter().any(|key| !self.before.contains(key))
+        }
+    }
+
+    fn key_binding(key: SerializableKeycode, action: TriggerAction) -> Binding {
+        Binding {
+            steps: vec![vec![key]],
+            action,
+            mode: TriggerMode::Press,
+        }
+    }
+
+    fn board_playing(note: u8, generator: Generator) -> BoardBindings {
+        let sound = Arc::new(Sound {
+            generator: Some(generator),
+            editing: false,
+            midi: Some(MidiTrigger::Note { channel: 0, note }),
+            ..Sound::default()
+        });
+
+        BoardBindings {
+            midi_bindings: vec![MidiBinding {
+                trigger: MidiTrigger::Note { channel: 0, note },
+                velocity_to_volume: false,
+                action: TriggerAction::PlaySound(Arc::clone(&sound)),
+            }],
+            pads: vec![sound],
+            ..BoardBindings::default()
+        }
+    }
+
+    #[test]
+    fn pads_follow_the_playback_and_the_board_hotkeys() {
+        let snapshot = TriggerSnapshot {
+            boards: vec![board_playing(60, Generator::default_tone()), board_playing(61, Generator::default_noise())],
+            feedback_profile: FeedbackProfile::apc_mini(),
+            ..TriggerSnapshot::default()
+        };
+        let profile = &snapshot.feedback_profile;
+        let shared = SharedSnapshot::default();
+        let mut files = FileCache::new();
+        let mut playback = PlaybackState::default();
+        let (first, second) = (MidiTrigger::Note { channel: 0, note: 60 }, MidiTrigger::Note { channel: 0, note: 61 });
+
+        let values = super::desktop::pad_feedback(&shared, &snapshot, &playback, &mut files);
+        assert_eq!(values, HashMap::from([(first, profile.idle)]));
+
+        playback.playing.insert(snapshot.boards[0].pads[0].key().unwrap());
+        let values = super::desktop::pad_feedback(&shared, &snapshot, &playback, &mut files);
+        assert_eq!(values, HashMap::from([(first, profile.playing)]));
+
+        // Board cambiata da una hotkey, con la UI ferma
+        shared.step_board(1, snapshot.boards.len());
+        let values = super::desktop::pad_feedback(&shared, &snapshot, &playback, &mut files);
+        assert_eq!(values, HashMap::from([(second, profile.idle)]));
+    }
+
+    #[test]
+    fn board_hotkeys_switch_the_bindings_without_the_ui() {
+        let snapshot = TriggerSnapshot {
+            boards: vec![board_playing(60, Generator::default_tone()), board_playing(60, Generator::default_noise())],
+            stop_on_new: true,
+            ..TriggerSnapshot::default()
+        };
+        let shared = SharedSnapshot::default();
+        let mut audio = NullAudio::new();
+        let pad = MidiMessage { trigger: MidiTrigger::Note { channel: 0, note: 60 }, value: 100 };
+        let key = |idx: usize| match &snapshot.boards[idx].midi_bindings[0].action {
+            TriggerAction::PlaySound(sound) => sound.key().unwrap(),
+            _ => unreachable!(),
+        };
+
+        fire_midi(&shared, &snapshot, pad, &mut audio);
+        fire(&shared, &snapshot, &TriggerAction::App(AppAction::NextBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 1);
+        fire_midi(&shared, &snapshot, pad, &mut audio);
+
+        // Si torna alla prima board passando dall'ultima
+        fire(&shared, &snapshot, &TriggerAction::App(AppAction::NextBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 0);
+        fire(&shared, &snapshot, &TriggerAction::App(AppAction::PreviousBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 1);
+
+        let played: Vec<_> = audio.history().iter().map(|record| record.key.clone()).collect();
+        assert_eq!(played, [key(0), key(1)]);
+    }
+
+    #[test]
+    fn restart_plays_the_sound_again_from_the_start() {
+        let (shared, snapshot, mut audio) = (SharedSnapshot::default(), TriggerSnapshot::default(), NullAudio::new());
+        let sound = synth(Generator::default_tone(), RetriggerPolicy::Restart);
+
+        press(&shared, &snapshot, &sound, &mut audio);
+        press(&shared, &snapshot, &sound, &mut audio);
+
+        assert_eq!(timeline(&audio), [(Duration::ZERO, Some(STEP)), (STEP, None)]);
+    }
+
+    #[test]
+    fn stop_ends_the_sound_on_the_second_press() {
+        let (shared, snapshot, mut audio) = (SharedSnapshot::default(), TriggerSnapshot::default(), NullAudio::new());
+        let sound = synth(Generator::default_tone(), RetriggerPolicy::Stop);
+
+        press(&shared, &snapshot, &sound, &mut audio);
+        press(&shared, &snapshot, &sound, &mut audio);
+        assert_eq!(timeline(&audio), [(Duration::ZERO, Some(STEP))]);
+
+        // Da fermo riparte
+        press(&shared, &snapshot, &sound, &mut audio);
+        assert_eq!(timeline(&audio), [(Duration::ZERO, Some(STEP)), (STEP * 2, None)]);
+    }
+
+    #[test]
+    fn ignore_lets_the_sound_finish() {
+        let (shared, snapshot, mut audio) = (SharedSnapshot::default(), TriggerSnapshot::default(), NullAudio::new());
+        let sound = synth(Generator::default_tone(), RetriggerPolicy::Ignore);
+
+        press(&shared, &snapshot, &sound, &mut audio);
+        press(&shared, &snapshot, &sound, &mut audio);
+
+        assert_eq!(timeline(&audio), [(Duration::ZERO, None)]);
+    }
+
+    #[test]
+    fn stack_layers_the_instances() {
+        let (shared, snapshot, mut audio) = (SharedSnapshot::default(), TriggerSnapshot::default(), NullAudio::new());
+        let sound = synth(Generator::default_tone(), RetriggerPolicy::Stack);
+
+        press(&shared, &snapshot, &sound, &mut audio);
+        press(&shared, &snapshot, &sound, &mut audio);
+
+        assert_eq!(timeline(&audio), [(Duration::ZERO, None), (STEP, None)]);
+        assert_eq!(audio.playing_keys().len(), 1);
+    }
+
+    #[test]
+    fn another_sound_waits_unless_interrupting_is_on() {
+        let shared = SharedSnapshot::default();
+        let mut audio = NullAudio::new();
+        let tone = synth(Generator::default_tone(), RetriggerPolicy::Restart);
+        let noise = synth(Generator::default_noise(), RetriggerPolicy::Restart);
+
+        let waiting = TriggerSnapshot {
+            boards: vec![BoardBindings::default()],
+            stop_on_new: false,
+            ..TriggerSnapshot::default()
+        };
+        press(&shared, &waiting, &tone, &mut audio);
+        press(&shared, &waiting, &noise, &mut audio);
+        assert_eq!(timeline(&audio), [(Duration::ZERO, None)]);
+
+        // La board puo' scavalcare l'impostazione globale
+        let interrupting = TriggerSnapshot {
+            boards: vec![BoardBindings { stop_on_new: Some(true), ..BoardBindings::default() }],
+            ..waiting
+        };
+        press(&shared, &interrupting, &noise, &mut audio);
+        assert_eq!(timeline(&audio), [(Duration::ZERO, Some(STEP * 2)), (STEP * 2, None)]);
+        assert_eq!(audio.playing_keys(), [noise.key().unwrap()].into());
+    }
+
+    #[test]
+    fn stop_all_silences_every_sound() {
+        let (shared, snapshot, mut audio) = (SharedSnapshot::default(), TriggerSnapshot::default(), NullAudio::new());
+        let sound = synth(Generator::default_tone(), RetriggerPolicy::Stack);
+
+        press(&shared, &snapshot, &sound, &mut audio);
+        press(&shared, &snapshot, &sound, &mut audio);
+        fire(&shared, &snapshot, &app(AppAction::StopAll), 1.0, &mut audio);
+
+        assert!(audio.playing_keys().is_empty());
+        assert_eq!(timeline(&audio), [(Duration::ZERO, Some(STEP * 2)), (STEP, Some(STEP * 2))]);
+    }
+
+    #[test]
+    fn mute_and_volume_reach_the_output() {
+        let (shared, snapshot, mut audio) = (SharedSnapshot::default(), TriggerSnapshot::default(), Headless::default());
+
+        fire(&shared, &snapshot, &app(AppAction::ToggleMute), 1.0, &mut audio);
+        assert!(audio.muted);
+        fire(&shared, &snapshot, &app(AppAction::VolumeUp), 1.0, &mut audio);
+        fire(&shared, &snapshot, &app(AppAction::VolumeDown), 1.0, &mut audio);
+        fire(&shared, &snapshot, &app(AppAction::ToggleMute), 1.0, &mut audio);
+
+        assert!(!audio.muted);
+        assert_eq!(audio.volume_steps, [VOLUME_STEP, -VOLUME_STEP]);
+    }
+
+    #[test]
+    fn the_record_hotkey_starts_and_stops_the_session() {
+        let shared = SharedSnapshot::default();
+        let snapshot = TriggerSnapshot {
+            recording: RecordingTarget { directory: PathBuf::from("takes"), ..RecordingTarget::default() },
+            ..TriggerSnapshot::default()
+        };
+        let mut audio = Headless::default();
+
+        fire(&shared, &snapshot, &TriggerAction::ToggleRecording, 1.0, &mut audio);
+        assert_eq!(audio.recording, Some(snapshot.recording.clone()));
+        fire(&shared, &snapshot, &TriggerAction::ToggleRecording, 1.0, &mut audio);
+        assert_eq!(audio.recording, None);
+    }
+
+    #[test]
+    fn board_hotkeys_wrap_around_and_need_boards() {
+        let shared = SharedSnapshot::default();
+        let mut audio = NullAudio::new();
+
+        // Senza board pubblicate non c'e' dove andare
+        fire(&shared, &TriggerSnapshot::default(), &app(AppAction::NextBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 0);
+
+        let snapshot = TriggerSnapshot {
+            boards: vec![BoardBindings::default(); 3],
+            ..TriggerSnapshot::default()
+        };
+        fire(&shared, &snapshot, &app(AppAction::PreviousBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 2);
+        fire(&shared, &snapshot, &app(AppAction::NextBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 0);
+        fire(&shared, &snapshot, &app(AppAction::NextBoard), 1.0, &mut audio);
+        assert_eq!(shared.active_board(), 1);
+    }
+
+    #[test]
+    fn disarmed_hotkeys_only_listen_for_the_arm_toggle() {
+        let shared = SharedSnapshot::default();
+        let snapshot = TriggerSnapshot::default();
+        let mut audio = Headless::default();
+        let sound = synth(Generator::default_tone(), RetriggerPolicy::Restart);
+
+        fire(&shared, &snapshot, &app(AppAction::ToggleHotkeys), 1.0, &mut audio);
+        assert!(!shared.armed());
+
+        fire(&shared, &snapshot, &TriggerAction::PlaySound(Arc::clone(&sound)), 1.0, &mut audio);
+        fire(&shared, &snapshot, &TriggerAction::ToggleRecording, 1.0, &mut audio);
+        fire(&shared, &snapshot, &app(AppAction::ToggleMute), 1.0, &mut audio);
+        fire(&shared, &snapshot, &app(AppAction::VolumeUp), 1.0, &mut audio);
+        assert!(audio.audio.history().is_empty());
+        assert_eq!((audio.recording.clone(), audio.muted, audio.volume_steps.len()), (None, false, 0));
+
+        fire(&shared, &snapshot, &app(AppAction::ToggleHotkeys), 1.0, &mut audio);
+        assert!(shared.armed());
+        fire(&shared, &snapshot, &TriggerAction::PlaySound(sound), 1.0, &mut audio);
+        assert_eq!(audio.audio.history().len(), 1);
+    }
+
+    #[test]
+    fn disarmed_midi_pads_stay_silent() {
+        let snapshot = TriggerSnapshot {
+            boards: vec![board_playing(60, Generator::default_tone())],
+            ..TriggerSnapshot::default()
+        };
+        let shared = SharedSnapshot::default();
+        let mut audio = NullAudio::new();
+        let pad = MidiMessage { trigger: MidiTrigger::Note { channel: 0, note: 60 }, value: 100 };
+
+        shared.set_armed(false);
+        fire_midi(&shared, &snapshot, pad, &mut audio);
+        assert!(audio.history().is_empty());
+
+        shared.set_armed(true);
+        fire_midi(&shared, &snapshot, pad, &mut audio);
+        assert_eq!(audio.history().len(), 1);
+    }
+
+    #[test]
+    fn the_keyboard_pauses_in_the_background_but_midi_does_not() {
+        let tone = synth(Generator::default_tone(), RetriggerPolicy::Stack);
+        let mut board = board_playing(60, Generator::default_noise());
+        board.bindings.push(key_binding(SerializableKeycode::F13, TriggerAction::PlaySound(Arc::clone(&tone))));
+        let paused = TriggerSnapshot {
+            boards: vec![board],
+            stop_on_new: false,
+            keyboard_paused: true,
+            ..TriggerSnapshot::default()
+        };
+        let shared = SharedSnapshot::default();
+        let mut keyboard = ScriptedKeyboard::default();
+        let mut triggers = KeyboardTriggers::new();
+        let mut audio = NullAudio::new();
+
+        // Il tasto premuto mentre la finestra e' in secondo piano non conta
+        keyboard.hold(&[SerializableKeycode::F13]);
+        triggers.poll(&keyboard, &shared, &paused, &mut audio);
+        assert!(audio.history().is_empty());
+
+        let pad = MidiMessage { trigger: MidiTrigger::Note { channel: 0, note: 60 }, value: 100 };
+        fire_midi(&shared, &paused, pad, &mut audio);
+        assert_eq!(audio.history().len(), 1);
+        audio.stop_all();
+
+        // Tornato il focus serve una nuova pressione
+        let focused = TriggerSnapshot { keyboard_paused: false, ..paused };
+        keyboard.hold(&[SerializableKeycode::F13]);
+        triggers.poll(&keyboard, &shared, &focused, &mut audio);
+        assert_eq!(audio.history().len(), 1);
+        keyboard.hold(&[]);
+        triggers.poll(&keyboard, &shared, &focused, &mut audio);
+        keyboard.hold(&[SerializableKeycode::F13]);
+        triggers.poll(&keyboard, &shared, &focused, &mut audio);
+        assert_eq!(audio.history().last().map(|record| record.key.clone()), tone.key());
+    }
+}
@@ -1,34 +1,163 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::shortcut::chord::{any_key_just_pressed, chord_just_pressed, chord_matches};
 use crate::shortcut::interface::ShortcutListener;
+use crate::shortcut::keycodes::SerializableKeycode;
 
 use wasm_bindgen::prelude::*;
-use web_sys::window;
+use web_sys::{window, KeyboardEvent, MouseEvent};
+
+/// Keys held according to the DOM events, updated by the listeners.
+#[derive(Default)]
+struct DomKeys {
+    held: Vec<SerializableKeycode>,
+    repaint: Option<egui::Context>,
+}
+
+impl DomKeys {
+    fn press(&mut self, key: SerializableKeycode) {
+        if !self.held.contains(&key) {
+            self.held.push(key);
+        }
+        self.request_repaint();
+    }
+
+    fn release(&mut self, key: &SerializableKeycode) {
+        self.held.retain(|held| held != key);
+        self.request_repaint();
+    }
+
+    fn request_repaint(&self) {
+        if let Some(ctx) = &self.repaint {
+            ctx.request_repaint();
+        }
+    }
+}
+
+thread_local! {
+    /// Shared by the trigger listener and the recorder, so both see the
+    /// same `KeyboardEvent.code`s.
+    static DOM_KEYS: Rc<RefCell<DomKeys>> = listen_to_dom();
+}
+
+/// Keys and mouse buttons held now, as the DOM reports them.
+pub fn pressed_inputs() -> Vec<SerializableKeycode> {
+    DOM_KEYS.with(|keys| keys.borrow().held.clone())
+}
 
 pub struct WebShortcutListener {
+    dom_keys: Rc<RefCell<DomKeys>>,
+    pressed_keys: Vec<SerializableKeycode>,
+    previous_keys: Vec<SerializableKeycode>,
 }
 
 impl ShortcutListener for WebShortcutListener {
-    fn is_pressed(&self, shortcut: &Vec<super::keycodes::SerializableKeycode>) -> bool {
-        false
+    fn update(&mut self) {
+        let current_keys = self.dom_keys.borrow().held.clone();
+        self.previous_keys = std::mem::replace(&mut self.pressed_keys, current_keys);
+    }
+
+    fn is_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+        chord_matches(shortcut, &self.pressed_keys)
+    }
+
+    fn just_pressed(&self, shortcut: &[SerializableKeycode]) -> bool {
+        chord_just_pressed(shortcut, &self.pressed_keys, &self.previous_keys)
+    }
+
+    fn any_key_just_pressed(&self) -> bool {
+        any_key_just_pressed(&self.pressed_keys, &self.previous_keys)
     }
 }
 
 impl WebShortcutListener {
     pub fn new() -> Self {
-        let window = window().unwrap();
-        let closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
-            web_sys::console::log_1(&format!("Key pressed: code = {}, key = {}", event.code(), event.key()).into());
-        }) as Box<dyn FnMut(_)>);
+        Self {
+            dom_keys: DOM_KEYS.with(Rc::clone),
+            pressed_keys: Vec::new(),
+            previous_keys: Vec::new(),
+        }
+    }
+
+    /// Repaints on every key change, so the shortcuts are checked right away.
+    pub fn set_repaint_context(&mut self, ctx: egui::Context) {
+        self.dom_keys.borrow_mut().repaint = Some(ctx);
+    }
+
+    pub fn get_pressed_keys(&self) -> Vec<SerializableKeycode> {
+        self.pressed_keys.clone()
+    }
+}
+
+/// Registers the DOM listeners, once per page.
+fn listen_to_dom() -> Rc<RefCell<DomKeys>> {
+    let window = window().unwrap();
+    let dom_keys = Rc::new(RefCell::new(DomKeys::default()));
 
+    let keys = Rc::clone(&dom_keys);
+    let keydown = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        if !event.repeat() {
+            keys.borrow_mut().press(SerializableKeycode::from_dom_code(&event.code()));
+        }
+    }) as Box<dyn FnMut(_)>);
 
+    let keys = Rc::clone(&dom_keys);
+    let keyup = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+        keys.borrow_mut().release(&SerializableKeycode::from_dom_code(&event.code()));
+    }) as Box<dyn FnMut(_)>);
+
+    let keys = Rc::clone(&dom_keys);
+    let mousedown = Closure::wrap(Box::new(move |event: MouseEvent| {
+        if let Some(button) = mouse_button(event.button()) {
+            keys.borrow_mut().press(button);
+        }
+    }) as Box<dyn FnMut(_)>);
+
+    let keys = Rc::clone(&dom_keys);
+    let mouseup = Closure::wrap(Box::new(move |event: MouseEvent| {
+        if let Some(button) = mouse_button(event.button()) {
+            keys.borrow_mut().release(&button);
+        }
+    }) as Box<dyn FnMut(_)>);
+
+    // Senza focus i keyup non arrivano: si dimentica tutto per non lasciare tasti bloccati
+    let keys = Rc::clone(&dom_keys);
+    let blur = Closure::wrap(Box::new(move |_: web_sys::Event| {
+        let mut keys = keys.borrow_mut();
+        keys.held.clear();
+        keys.request_repaint();
+    }) as Box<dyn FnMut(_)>);
+
+    let listeners: [(&str, &JsValue); 5] = [
+        ("keydown", keydown.as_ref()),
+        ("keyup", keyup.as_ref()),
+        ("mousedown", mousedown.as_ref()),
+        ("mouseup", mouseup.as_ref()),
+        ("blur", blur.as_ref()),
+    ];
+    for (event, callback) in listeners {
         window
-            .add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())
+            .add_event_listener_with_callback(event, callback.unchecked_ref())
             .unwrap();
+    }
 
-        closure.forget(); // Prevent the closure from being dropped
+    // Il listener vive quanto la pagina
+    keydown.forget();
+    keyup.forget();
+    mousedown.forget();
+    mouseup.forget();
+    blur.forget();
 
-        Self {}
-    }
+    dom_keys
+}
 
-    pub fn update(&mut self) {
+/// The shortcut key for a DOM `MouseEvent.button`, if it can be bound.
+fn mouse_button(button: i16) -> Option<SerializableKeycode> {
+    match button {
+        1 => Some(SerializableKeycode::MouseMiddle),
+        3 => Some(SerializableKeycode::MouseBack),
+        4 => Some(SerializableKeycode::MouseForward),
+        _ => None,
     }
-}
\ No newline at end of file
+}
@@ -1,13 +1,203 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::audio::synth::Generator;
+use crate::gamepad::GamepadTrigger;
+use crate::midi::MidiTrigger;
 use crate::shortcut::keycodes::SerializableKeycode;
 
 #[derive(serde::Deserialize, serde::Serialize)]
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Sound {
     pub name: Option<String>,
     pub path: Option<String>,
     pub shortcut: Option<Vec<SerializableKeycode>>,
+    /// Further chords to press after `shortcut`, for leader-key style bindings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequence: Vec<Vec<SerializableKeycode>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub midi: Option<MidiTrigger>,
+    /// Scale the volume by the velocity of the MIDI note that triggers it.
+    #[serde(default)]
+    pub velocity_to_volume: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gamepad: Option<GamepadTrigger>,
     pub editing: bool,
     pub data: Option<Vec<u8>>,
+    pub generator: Option<Generator>,
+    #[serde(default)]
+    pub retrigger: RetriggerPolicy,
+    #[serde(default)]
+    pub trigger_mode: TriggerMode,
+    /// Not saved: every sound gets a new one when it is loaded.
+    #[serde(skip)]
+    pub id: SoundId,
+}
+
+/// Tells apart sounds with the same settings while the app runs, so two
+/// pads with the same generator do not share their playback.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SoundId(u64);
+
+impl SoundId {
+    pub fn new() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for SoundId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for SoundId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// What a shortcut does when its sound is already playing.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RetriggerPolicy {
+    /// Start the sound again from the beginning.
+    Restart,
+    /// Let the current playback finish.
+    Ignore,
+    /// Play another instance on top of the current one.
+    Stack,
+    /// Stop the sound.
+    #[default]
+    Stop,
+}
+
+impl RetriggerPolicy {
+    pub const ALL: [RetriggerPolicy; 4] = [
+        RetriggerPolicy::Restart,
+        RetriggerPolicy::Ignore,
+        RetriggerPolicy::Stack,
+        RetriggerPolicy::Stop,
+    ];
+}
+
+impl std::fmt::Display for RetriggerPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            RetriggerPolicy::Restart => "Restart",
+            RetriggerPolicy::Ignore => "Ignore",
+            RetriggerPolicy::Stack => "Stack",
+            RetriggerPolicy::Stop => "Stop",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// How the keyboard shortcut of a sound has to be pressed.
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TriggerMode {
+    /// Play as soon as the shortcut goes down.
+    #[default]
+    Press,
+    /// Play while the shortcut is held and stop on release.
+    Gate,
+    /// A short press, not followed by a second one.
+    Tap,
+    DoubleTap,
+    LongPress,
+}
+
+impl TriggerMode {
+    pub const ALL: [TriggerMode; 5] = [
+        TriggerMode::Press,
+        TriggerMode::Gate,
+        TriggerMode::Tap,
+        TriggerMode::DoubleTap,
+        TriggerMode::LongPress,
+    ];
+
+    /// Tap, double tap and long press: the shortcut has to be told apart
+    /// from the other gestures on the same keys, so they can share it.
+    pub fn is_gesture(self) -> bool {
+        matches!(self, TriggerMode::Tap | TriggerMode::DoubleTap | TriggerMode::LongPress)
+    }
+}
+
+impl std::fmt::Display for TriggerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TriggerMode::Press => "Press",
+            TriggerMode::Gate => "Hold",
+            TriggerMode::Tap => "Tap",
+            TriggerMode::DoubleTap => "Double tap",
+            TriggerMode::LongPress => "Long press",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Sound {
+    /// Identifies the sound in the audio backend: its [`SoundId`] for
+    /// synthesized sounds, the file path otherwise.
+    pub fn key(&self) -> Option<String> {
+        match &self.generator {
+            Some(_) => Some(format!("synth:{}", self.id)),
+            None => self.path.clone(),
+        }
+    }
+
+    /// A copy without the file contents: playback reads the file from `path`.
+    pub fn without_data(&self) -> Sound {
+        let Sound {
+            name, path, shortcut, sequence, midi, velocity_to_volume, gamepad, editing,
+            data: _, generator, retrigger, trigger_mode, id,
+        } = self;
+
+        Sound {
+            name: name.clone(),
+            path: path.clone(),
+            shortcut: shortcut.clone(),
+            sequence: sequence.clone(),
+            midi: *midi,
+            velocity_to_volume: *velocity_to_volume,
+            gamepad: *gamepad,
+            editing: *editing,
+            data: None,
+            generator: generator.clone(),
+            retrigger: *retrigger,
+            trigger_mode: *trigger_mode,
+            id: *id,
+        }
+    }
+
+    /// Compares every field but the file contents, which can be large.
+    pub fn eq_without_data(&self, other: &Sound) -> bool {
+        let Sound {
+            name, path, shortcut, sequence, midi, velocity_to_volume, gamepad, editing,
+            data: _, generator, retrigger, trigger_mode, id,
+        } = self;
+
+        *name == other.name
+            && *path == other.path
+            && *shortcut == other.shortcut
+            && *sequence == other.sequence
+            && *midi == other.midi
+            && *velocity_to_volume == other.velocity_to_volume
+            && *gamepad == other.gamepad
+            && *editing == other.editing
+            && *generator == other.generator
+            && *retrigger == other.retrigger
+            && *trigger_mode == other.trigger_mode
+            && *id == other.id
+    }
+
+    /// Every chord of the shortcut in order, `None` when it has none.
+    pub fn steps(&self) -> Option<Vec<Vec<SerializableKeycode>>> {
+        let first = self.shortcut.clone()?;
+        Some(std::iter::once(first).chain(self.sequence.iter().cloned()).collect())
+    }
 }
 
 impl Default for Sound {
@@ -16,8 +206,53 @@ impl Default for Sound {
             name: None,
             path: None,
             shortcut: None,
+            sequence: Vec::new(),
+            midi: None,
+            velocity_to_volume: false,
+            gamepad: None,
             editing: true,
-            data: None
+            data: None,
+            generator: None,
+            retrigger: RetriggerPolicy::default(),
+            trigger_mode: TriggerMode::default(),
+            id: SoundId::new(),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn without_data_only_drops_the_file_contents() {
+        let sound = Sound {
+            name: Some("Applause".to_string()),
+            path: Some("applause.wav".to_string()),
+            shortcut: Some(vec![SerializableKeycode::A]),
+            data: Some(vec![1, 2, 3]),
+            retrigger: RetriggerPolicy::Stack,
+            ..Sound::default()
+        };
+        let copy = sound.without_data();
+
+        assert_eq!(copy.data, None);
+        assert_eq!(copy, Sound { data: None, ..sound.clone() });
+        assert!(copy.eq_without_data(&sound));
+        assert!(!copy.eq_without_data(&Sound { retrigger: RetriggerPolicy::Stop, ..sound }));
+    }
+
+    #[test]
+    fn synthesized_sounds_are_keyed_by_their_id() {
+        let generator = Some(Generator::default_tone());
+        let first = Sound { generator: generator.clone(), ..Sound::default() };
+        let second = Sound { generator, ..Sound::default() };
+
+        // Stesso generatore, suoni diversi: ognuno ha la sua riproduzione
+        assert_ne!(first.key(), second.key());
+        assert_eq!(first.key(), first.clone().key());
+        assert_eq!(first.key(), first.without_data().key());
+
+        let clip = Sound { path: Some("applause.wav".to_string()), ..Sound::default() };
+        assert_eq!(clip.key().as_deref(), Some("applause.wav"));
+    }
+}
@@ -5,13 +5,36 @@ pub struct RowLocation {
     pub row: usize,
 }
 
+/// A chord such as `Ctrl+Shift+1`; [`parse_shortcut`] reads it back.
 pub fn shortcut_as_string(keys: &[SerializableKeycode]) -> String {
     keys.iter()
-        .map(|k| format!("{}", k).trim_matches('"').to_string())
+        .map(|k| k.to_string())
         .collect::<Vec<_>>()
         .join("+")
 }
 
+/// Steps of a multi-chord shortcut, e.g. `F13, 3`.
+pub fn sequence_as_string(steps: &[Vec<SerializableKeycode>]) -> String {
+    steps.iter()
+        .map(|step| shortcut_as_string(step))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Parses a chord written by [`shortcut_as_string`] or typed by the user.
+pub fn parse_shortcut(text: &str) -> Result<Vec<SerializableKeycode>, String> {
+    if text.trim().is_empty() {
+        return Err("The shortcut is empty".to_string());
+    }
+
+    text.split('+').map(str::parse).collect()
+}
+
+/// Parses the steps written by [`sequence_as_string`].
+pub fn parse_sequence(text: &str) -> Result<Vec<Vec<SerializableKeycode>>, String> {
+    text.split(',').map(parse_shortcut).collect()
+}
+
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
@@ -20,4 +43,39 @@ use wasm_bindgen::prelude::*;
 #[wasm_bindgen]
 extern "C" {
     pub fn trigger_file_picker(row : usize);
+
+    pub fn trigger_board_picker();
+
+    pub fn download_file(name: &str, data: &[u8]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use SerializableKeycode::*;
+
+    #[test]
+    fn shortcuts_round_trip() {
+        let shortcuts = [
+            vec![Ctrl, Shift, Key1],
+            vec![LControl, Comma],
+            vec![Alt, NumpadAdd],
+            vec![NumpadSubtract],
+            vec![Super, Unknown("IntlRo".to_string())],
+        ];
+
+        for shortcut in &shortcuts {
+            assert_eq!(parse_shortcut(&shortcut_as_string(shortcut)).as_ref(), Ok(shortcut));
+        }
+
+        let steps = shortcuts.to_vec();
+        assert_eq!(parse_sequence(&sequence_as_string(&steps)), Ok(steps));
+    }
+
+    #[test]
+    fn typed_shortcuts_ignore_spaces_and_case() {
+        assert_eq!(parse_shortcut("ctrl + shift + a"), Ok(vec![Ctrl, Shift, A]));
+        assert_eq!(parse_sequence("F13, 3"), Ok(vec![vec![F13], vec![Key3]]));
+        assert!(parse_shortcut("  ").is_err());
+    }
 }
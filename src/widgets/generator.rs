@@ -0,0 +1,88 @@
+use egui::{ComboBox, DragValue, Response, Ui, Widget};
+
+use crate::audio::synth::{Generator, NoiseColor, Waveform};
+
+/// Lets the user pick between a sound file and one of the built-in generators,
+/// and edit the generator parameters.
+pub struct GeneratorEditor<'a> {
+    pub generator: &'a mut Option<Generator>,
+    pub id: usize,
+}
+
+impl<'a> GeneratorEditor<'a> {
+    pub fn new(generator: &'a mut Option<Generator>, id: usize) -> Self {
+        Self { generator, id }
+    }
+}
+
+fn source_label(generator: &Option<Generator>) -> &'static str {
+    match generator {
+        None => "File",
+        Some(Generator::Tone { .. }) => "Tone",
+        Some(Generator::Noise { .. }) => "Noise",
+        Some(Generator::Dtmf { .. }) => "DTMF",
+        Some(Generator::Countdown { .. }) => "Countdown",
+    }
+}
+
+impl Widget for GeneratorEditor<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.horizontal(|ui| {
+            let current = source_label(self.generator);
+
+            ComboBox::from_id_salt(("sound_source", self.id))
+                .selected_text(current)
+                .width(0.0)
+                .show_ui(ui, |ui| {
+                    let choices = [
+                        None,
+                        Some(Generator::default_tone()),
+                        Some(Generator::default_noise()),
+                        Some(Generator::default_dtmf()),
+                        Some(Generator::default_countdown()),
+                    ];
+
+                    for choice in choices {
+                        let label = source_label(&choice);
+                        if ui.selectable_label(current == label, label).clicked() && current != label {
+                            *self.generator = choice;
+                        }
+                    }
+                });
+
+            match self.generator {
+                None => {}
+                Some(Generator::Tone { waveform, frequency, duration_ms }) => {
+                    ComboBox::from_id_salt(("tone_waveform", self.id))
+                        .selected_text(format!("{waveform:?}"))
+                        .width(0.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(waveform, Waveform::Sine, "Sine");
+                            ui.selectable_value(waveform, Waveform::Square, "Square");
+                        });
+                    ui.add(DragValue::new(frequency).range(20.0..=20_000.0).suffix(" Hz"));
+                    ui.add(DragValue::new(duration_ms).range(10..=60_000).suffix(" ms"));
+                }
+                Some(Generator::Noise { color, duration_ms }) => {
+                    ComboBox::from_id_salt(("noise_color", self.id))
+                        .selected_text(format!("{color:?}"))
+                        .width(0.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(color, NoiseColor::White, "White");
+                            ui.selectable_value(color, NoiseColor::Pink, "Pink");
+                        });
+                    ui.add(DragValue::new(duration_ms).range(10..=60_000).suffix(" ms"));
+                }
+                Some(Generator::Dtmf { digits }) => {
+                    ui.text_edit_singleline(digits)
+                        .on_hover_text("Digits 0-9, A-D, * and #");
+                }
+                Some(Generator::Countdown { beeps, interval_ms }) => {
+                    ui.add(DragValue::new(beeps).range(1..=10).suffix(" beeps"));
+                    ui.add(DragValue::new(interval_ms).range(200..=5_000).suffix(" ms"));
+                }
+            }
+        })
+        .response
+    }
+}
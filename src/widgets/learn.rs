@@ -0,0 +1,86 @@
+use egui::{Response, Ui, Widget};
+
+use crate::shortcut::monitor::InputMonitor;
+
+/// Binds a MIDI note, a gamepad button or any other learnable input to a
+/// sound: click, then press the control.
+pub struct InputLearn<'a, T> {
+    pub trigger: &'a mut Option<T>,
+    pub learning: &'a mut Option<usize>,
+    pub monitor: &'a InputMonitor<T>,
+    pub id: usize,
+    pub empty_text: &'static str,
+    pub prompt: &'static str,
+}
+
+impl<'a, T> InputLearn<'a, T> {
+    pub fn new(
+        trigger: &'a mut Option<T>,
+        learning: &'a mut Option<usize>,
+        monitor: &'a InputMonitor<T>,
+        id: usize,
+    ) -> Self {
+        Self {
+            trigger,
+            learning,
+            monitor,
+            id,
+            empty_text: "Not bound",
+            prompt: "Waiting for input...",
+        }
+    }
+
+    /// Label shown while nothing is bound.
+    pub fn empty_text(mut self, empty_text: &'static str) -> Self {
+        self.empty_text = empty_text;
+        self
+    }
+
+    /// Label shown while waiting for the input.
+    pub fn prompt(mut self, prompt: &'static str) -> Self {
+        self.prompt = prompt;
+        self
+    }
+}
+
+impl<T: std::fmt::Display> Widget for InputLearn<'_, T> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let is_learning = *self.learning == Some(self.id);
+
+        if is_learning && let Some(trigger) = self.monitor.take() {
+            *self.trigger = Some(trigger);
+            *self.learning = None;
+        }
+
+        let label = if *self.learning == Some(self.id) {
+            self.prompt.to_string()
+        } else if let Some(trigger) = self.trigger {
+            trigger.to_string()
+        } else {
+            self.empty_text.to_string()
+        };
+
+        let mut response = ui.horizontal(|ui| {
+            let response = ui.button(label);
+
+            if response.clicked() {
+                // Scarta gli input arrivati prima del click
+                self.monitor.take();
+                *self.learning = Some(self.id);
+            }
+
+            if self.trigger.is_some() && ui.button("❌").on_hover_text("Rimuovi").clicked() {
+                *self.trigger = None;
+                *self.learning = None;
+            }
+
+            response
+        }).inner;
+
+        if is_learning && *self.learning != Some(self.id) {
+            response.mark_changed();
+        }
+
+        response
+    }
+}
@@ -1,3 +1,5 @@
 
 pub mod shortcut;
-pub mod settings;
\ No newline at end of file
+pub mod settings;
+pub mod generator;
+pub mod learn;
\ No newline at end of file
@@ -2,14 +2,30 @@ use egui::*;
 
 use crate::audio::{
     get_output_devices,
-    get_default_output_device
+    get_default_output_device,
+    default_recordings_dir,
+    AudioBackendKind,
+    RecordingFormat,
+    RecordingTarget,
 };
+use crate::midi::feedback::FeedbackProfile;
+use crate::midi::{PlatformMidiInput, PlatformMidiOutput};
+use crate::shortcut::ShortcutBackendKind;
+use crate::shortcut::action::{ActionShortcuts, AppAction};
+use crate::shortcut::conflict::ShortcutOwner;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::board::archive::default_library_dir;
+use crate::shortcut::gesture::GestureTiming;
+use crate::shortcut::keycodes::SerializableKeycode;
+use crate::shortcut::layout::KeyboardLayout;
+use crate::widgets::shortcut::PlatformShortcutRecorder;
 
 #[derive(serde::Deserialize, serde::Serialize)]
 #[derive(PartialEq, Clone, Copy)]
 pub enum SettingsTab {
     General,
     Audio,
+    Midi,
     Video,
 }
 
@@ -18,6 +34,7 @@ impl std::fmt::Display for SettingsTab {
         let s = match self {
             SettingsTab::General => "General",
             SettingsTab::Audio => "Audio",
+            SettingsTab::Midi => "MIDI",
             SettingsTab::Video => "Video",
         };
         write!(f, "{}", s)
@@ -32,11 +49,47 @@ pub struct SettingsWindow {
     selected_tab: SettingsTab,
     pub output_devices: Vec<String>,
     pub selected_device_name: Option<String>,
-    pub toggle_to_stop: bool,
+    pub audio_backend: AudioBackendKind,
     pub stop_on_new: bool,
+    pub side_agnostic_modifiers: bool,
+    pub focus_only: bool,
+    pub shortcut_backend: ShortcutBackendKind,
+    pub keyboard_layout: KeyboardLayout,
+    pub sequence_timeout_ms: u64,
+    pub double_tap_ms: u64,
+    pub long_press_ms: u64,
+    pub recordings_dir: Option<String>,
+    pub recording_format: RecordingFormat,
+    pub library_dir: Option<String>,
+    pub record_shortcut: Option<Vec<SerializableKeycode>>,
+    pub action_shortcuts: ActionShortcuts,
+    pub master_volume: f32,
+    pub midi_port: Option<String>,
+    pub midi_output_port: Option<String>,
+    pub feedback_profile: FeedbackProfile,
     
     #[serde(skip)]
-    pub new_device: Option<String>
+    pub new_device: Option<String>,
+    #[serde(skip)]
+    pub new_backend: Option<AudioBackendKind>,
+    #[serde(skip)]
+    pub new_midi_port: Option<Option<String>>,
+    #[serde(skip)]
+    midi_ports: Vec<String>,
+    #[serde(skip)]
+    pub new_midi_output_port: Option<Option<String>>,
+    #[serde(skip)]
+    midi_output_ports: Vec<String>,
+    /// Why the selected shortcut backend cannot read the keys.
+    #[serde(skip)]
+    pub shortcut_backend_error: Option<String>,
+    /// Set when one of the hotkey recorders in this window finishes.
+    #[serde(skip)]
+    pub changed_shortcut: Option<ShortcutOwner>,
+    #[serde(skip)]
+    listening_shortcut: Option<usize>,
+    #[serde(skip)]
+    last_pressed_keys: Option<Vec<SerializableKeycode>>,
 }
 
 impl Default for SettingsWindow {
@@ -46,14 +99,89 @@ impl Default for SettingsWindow {
             selected_tab: SettingsTab::General,
             output_devices: get_output_devices(),
             selected_device_name: get_default_output_device(),
-            toggle_to_stop: true,
+            audio_backend: AudioBackendKind::Device,
             stop_on_new: true,
-            new_device: None
+            side_agnostic_modifiers: true,
+            focus_only: false,
+            shortcut_backend: ShortcutBackendKind::default(),
+            keyboard_layout: KeyboardLayout::default(),
+            sequence_timeout_ms: 1000,
+            double_tap_ms: 300,
+            long_press_ms: 500,
+            recordings_dir: None,
+            recording_format: RecordingFormat::default(),
+            library_dir: None,
+            record_shortcut: None,
+            action_shortcuts: ActionShortcuts::default(),
+            master_volume: 1.0,
+            midi_port: None,
+            midi_output_port: None,
+            feedback_profile: FeedbackProfile::default(),
+            shortcut_backend_error: None,
+            new_device: None,
+            new_backend: None,
+            new_midi_port: None,
+            midi_ports: Vec::new(),
+            new_midi_output_port: None,
+            midi_output_ports: Vec::new(),
+            changed_shortcut: None,
+            listening_shortcut: None,
+            last_pressed_keys: None,
         }
     }
 }
 
 impl SettingsWindow {
+    pub fn recordings_dir(&self) -> std::path::PathBuf {
+        self.recordings_dir
+            .as_ref()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(default_recordings_dir)
+    }
+
+    pub fn recording_target(&self) -> RecordingTarget {
+        RecordingTarget {
+            directory: self.recordings_dir(),
+            format: self.recording_format,
+        }
+    }
+
+    /// Where imported boards unpack their sounds.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn library_dir(&self) -> std::path::PathBuf {
+        self.library_dir
+            .as_ref()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_else(default_library_dir)
+    }
+
+    pub fn sequence_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.sequence_timeout_ms)
+    }
+
+    pub fn gesture_timing(&self) -> GestureTiming {
+        GestureTiming {
+            double_tap: std::time::Duration::from_millis(self.double_tap_ms),
+            long_press: std::time::Duration::from_millis(self.long_press_ms),
+        }
+    }
+
+    /// Opens the Audio tab with the record hotkey recorder already listening.
+    pub fn reassign_record_shortcut(&mut self) {
+        self.open = true;
+        self.selected_tab = SettingsTab::Audio;
+        self.listening_shortcut = Some(0);
+        self.last_pressed_keys = None;
+    }
+
+    /// Opens the General tab with the recorder of `action` already listening.
+    pub fn reassign_action_shortcut(&mut self, action: AppAction) {
+        self.open = true;
+        self.selected_tab = SettingsTab::General;
+        self.listening_shortcut = Some(action_recorder_id(action));
+        self.last_pressed_keys = None;
+    }
+
     pub fn show(&mut self, ctx: &Context, _frame: &eframe::Frame) {
         if !self.open {
             return;
@@ -64,12 +192,16 @@ impl SettingsWindow {
             .show(ctx, |ui| {
                 // Tabs
                 ui.horizontal(|ui| {
-                    for tab in [SettingsTab::General, SettingsTab::Audio, SettingsTab::Video] {
+                    for tab in [SettingsTab::General, SettingsTab::Audio, SettingsTab::Midi, SettingsTab::Video] {
                         if ui
                             .selectable_label(self.selected_tab == tab, tab.to_string())
                             .clicked()
                         {
                             self.selected_tab = tab;
+                            if tab == SettingsTab::Midi {
+                                self.midi_ports = PlatformMidiInput::port_names();
+                                self.midi_output_ports = PlatformMidiOutput::port_names();
+                            }
                         }
                     }
                 });
@@ -78,12 +210,91 @@ impl SettingsWindow {
 
                 match self.selected_tab {
                     SettingsTab::General => {
-                        ui.checkbox(&mut self.toggle_to_stop, "Toggle to stop")
-                            .on_hover_text("Clicking again on a playing sound will stop it.");
-
                         ui.checkbox(&mut self.stop_on_new, "Interrupt on new")
                             .on_hover_text("Stops the currently playing sound when a new one is triggered.");
 
+                        ui.checkbox(&mut self.side_agnostic_modifiers, "Side-agnostic modifiers")
+                            .on_hover_text("Record Ctrl, Shift, Alt and Super so that either the left or the right key triggers the shortcut.");
+
+                        // Nel browser i tasti arrivano solo alla pagina col focus
+                        if cfg!(not(target_arch = "wasm32")) {
+                            ui.checkbox(&mut self.focus_only, "Only while focused")
+                                .on_hover_text("Ignore the keyboard shortcuts while another window is focused, e.g. while typing in a chat. MIDI and gamepads keep working.");
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label("Sequence timeout:");
+                            ui.add(egui::DragValue::new(&mut self.sequence_timeout_ms)
+                                .range(100..=5000)
+                                .speed(10)
+                                .suffix(" ms"))
+                                .on_hover_text("Longest pause between the steps of a multi-key shortcut.");
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Double tap within:");
+                            ui.add(egui::DragValue::new(&mut self.double_tap_ms)
+                                .range(100..=1000)
+                                .speed(10)
+                                .suffix(" ms"))
+                                .on_hover_text("Longest pause between the two presses of a double tap. A tap on a key that also has a double tap waits this long before firing.");
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Long press after:");
+                            ui.add(egui::DragValue::new(&mut self.long_press_ms)
+                                .range(200..=3000)
+                                .speed(10)
+                                .suffix(" ms"))
+                                .on_hover_text("How long a key has to be held to count as a long press.");
+                        });
+
+                        // Su Wayland device_query non vede i tasti
+                        if cfg!(target_os = "linux") {
+                            ui.horizontal(|ui| {
+                                ui.label("Shortcut input:");
+                                egui::ComboBox::from_id_salt("shortcut_backend")
+                                    .selected_text(backend_label(self.shortcut_backend))
+                                    .show_ui(ui, |ui| {
+                                        for kind in [ShortcutBackendKind::DeviceQuery, ShortcutBackendKind::Evdev] {
+                                            if ui.selectable_value(&mut self.shortcut_backend, kind, backend_label(kind)).clicked() {
+                                                self.shortcut_backend_error = kind.check().err();
+                                            }
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text("Where global shortcuts are read from. Use input devices under Wayland, or for the mouse side buttons.");
+                            });
+
+                            if let Some(error) = &self.shortcut_backend_error {
+                                ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {error}"));
+                            }
+                        }
+
+                        ui.separator();
+                        ui.label("Hotkeys:");
+
+                        egui::Grid::new("action_shortcuts").num_columns(2).show(ui, |ui| {
+                            for action in AppAction::ALL {
+                                ui.label(action.to_string());
+
+                                let response = ui.add(PlatformShortcutRecorder::new(
+                                    self.action_shortcuts.get_mut(action),
+                                    &mut self.listening_shortcut,
+                                    &mut self.last_pressed_keys,
+                                    action_recorder_id(action)
+                                )
+                                .side_agnostic(self.side_agnostic_modifiers)
+                                .backend(self.shortcut_backend)
+                                .layout(&self.keyboard_layout));
+
+                                if response.changed() {
+                                    self.changed_shortcut = Some(ShortcutOwner::Action(action));
+                                }
+                                ui.end_row();
+                            }
+                        });
+
                         ui.label(
                             RichText::new("ℹ Hover over a setting to see its description")
                                 .small()
@@ -91,6 +302,23 @@ impl SettingsWindow {
                         );
                     }
                     SettingsTab::Audio => {
+                        ui.label("Audio backend:");
+                        egui::ComboBox::from_id_salt("audio_backend")
+                            .selected_text(match self.audio_backend {
+                                AudioBackendKind::Device => "Sound card",
+                                AudioBackendKind::Null => "Null (no output)",
+                            })
+                            .show_ui(ui, |ui| {
+                                for (kind, label) in [
+                                    (AudioBackendKind::Device, "Sound card"),
+                                    (AudioBackendKind::Null, "Null (no output)"),
+                                ] {
+                                    if ui.selectable_value(&mut self.audio_backend, kind, label).clicked() {
+                                        self.new_backend = Some(kind);
+                                    }
+                                }
+                            });
+
                         ui.label("1. Output Audio Device:");
                         egui::ComboBox::from_id_salt("1_output_audio_device")
                             .selected_text(
@@ -129,7 +357,135 @@ impl SettingsWindow {
                                 }
                             });
                         */
+
+                        ui.separator();
+
+                        ui.label("Session recordings folder:");
+                        ui.horizontal(|ui| {
+                            let directory = self.recordings_dir
+                                .clone()
+                                .unwrap_or_else(|| default_recordings_dir().display().to_string());
+                            ui.add(egui::Label::new(directory).wrap_mode(egui::TextWrapMode::Truncate));
+
+                            #[cfg(not(target_arch = "wasm32"))]
+                            if ui.button(crate::icons::LOAD_EMOJI).clicked()
+                                && let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                self.recordings_dir = Some(path.display().to_string());
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label("Recording format:");
+                            egui::ComboBox::from_id_salt("recording_format")
+                                .selected_text(recording_format_label(self.recording_format))
+                                .show_ui(ui, |ui| {
+                                    for format in [RecordingFormat::Wav, RecordingFormat::Flac] {
+                                        ui.selectable_value(&mut self.recording_format, format, recording_format_label(format));
+                                    }
+                                });
+                        });
+
+                        // Nel browser i suoni importati restano in memoria
+                        #[cfg(not(target_arch = "wasm32"))]
+                        {
+                            ui.label("Imported sounds folder:");
+                            ui.horizontal(|ui| {
+                                let directory = self.library_dir
+                                    .clone()
+                                    .unwrap_or_else(|| default_library_dir().display().to_string());
+                                ui.add(egui::Label::new(directory).wrap_mode(egui::TextWrapMode::Truncate));
+
+                                if ui.button(crate::icons::LOAD_EMOJI).clicked()
+                                    && let Some(path) = rfd::FileDialog::new().pick_folder() {
+                                    self.library_dir = Some(path.display().to_string());
+                                }
+                            });
+                        }
+
+                        ui.horizontal(|ui| {
+                            ui.label("Start / stop recording:");
+                            let response = ui.add(PlatformShortcutRecorder::new(
+                                &mut self.record_shortcut,
+                                &mut self.listening_shortcut,
+                                &mut self.last_pressed_keys,
+                                0
+                            )
+                            .side_agnostic(self.side_agnostic_modifiers)
+                            .backend(self.shortcut_backend)
+                            .layout(&self.keyboard_layout));
+
+                            if response.changed() {
+                                self.changed_shortcut = Some(ShortcutOwner::ToggleRecording);
+                            }
+                        });
                     },
+                    SettingsTab::Midi => {
+                        ui.label("Input port:");
+                        ui.horizontal(|ui| {
+                            egui::ComboBox::from_id_salt("midi_input_port")
+                                .selected_text(self.midi_port.as_deref().unwrap_or("<none>"))
+                                .show_ui(ui, |ui| {
+                                    if ui.selectable_value(&mut self.midi_port, None, "<none>").clicked() {
+                                        self.new_midi_port = Some(None);
+                                    }
+                                    for port in &self.midi_ports {
+                                        if ui.selectable_value(&mut self.midi_port, Some(port.clone()), port).clicked() {
+                                            self.new_midi_port = Some(Some(port.clone()));
+                                        }
+                                    }
+                                });
+
+                            if ui.button("🔄").on_hover_text("Refresh the list of MIDI ports").clicked() {
+                                self.midi_ports = PlatformMidiInput::port_names();
+                                self.midi_output_ports = PlatformMidiOutput::port_names();
+                            }
+                        });
+
+                        ui.separator();
+
+                        ui.label("Feedback port:")
+                            .on_hover_text("Lights up the pads to show which are assigned and which are playing.");
+                        egui::ComboBox::from_id_salt("midi_output_port")
+                            .selected_text(self.midi_output_port.as_deref().unwrap_or("<none>"))
+                            .show_ui(ui, |ui| {
+                                if ui.selectable_value(&mut self.midi_output_port, None, "<none>").clicked() {
+                                    self.new_midi_output_port = Some(None);
+                                }
+                                for port in &self.midi_output_ports {
+                                    if ui.selectable_value(&mut self.midi_output_port, Some(port.clone()), port).clicked() {
+                                        self.new_midi_output_port = Some(Some(port.clone()));
+                                    }
+                                }
+                            });
+
+                        ui.label("Device profile:");
+                        egui::ComboBox::from_id_salt("midi_feedback_profile")
+                            .selected_text(self.feedback_profile.name.clone())
+                            .show_ui(ui, |ui| {
+                                for preset in FeedbackProfile::presets() {
+                                    let name = preset.name.clone();
+                                    ui.selectable_value(&mut self.feedback_profile, preset, name);
+                                }
+                            });
+
+                        // I valori sono velocity: ogni controller li mappa sui suoi colori
+                        ui.horizontal(|ui| {
+                            for (label, value) in [
+                                ("Idle", &mut self.feedback_profile.idle),
+                                ("Playing", &mut self.feedback_profile.playing),
+                                ("Missing", &mut self.feedback_profile.missing),
+                            ] {
+                                ui.label(label);
+                                ui.add(egui::DragValue::new(value).range(0..=127));
+                            }
+                        });
+
+                        ui.label(
+                            RichText::new("ℹ Bind notes or CCs to sounds with the MIDI button while editing a sound")
+                                .small()
+                                .color(ui.visuals().weak_text_color()),
+                        );
+                    }
                     SettingsTab::Video => { 
                         ui.label(
                             RichText::new("⚠ Work in progress")
@@ -140,3 +496,22 @@ impl SettingsWindow {
             });
     }
 }
+
+/// Id of the recorder of `action`; 0 is the record hotkey.
+fn action_recorder_id(action: AppAction) -> usize {
+    1 + AppAction::ALL.iter().position(|a| *a == action).unwrap_or_default()
+}
+
+fn recording_format_label(format: RecordingFormat) -> &'static str {
+    match format {
+        RecordingFormat::Wav => "WAV (32-bit float)",
+        RecordingFormat::Flac => "FLAC (24-bit)",
+    }
+}
+
+fn backend_label(kind: ShortcutBackendKind) -> &'static str {
+    match kind {
+        ShortcutBackendKind::DeviceQuery => "X11 (device_query)",
+        ShortcutBackendKind::Evdev => "Input devices (evdev)",
+    }
+}
@@ -1,4 +1,7 @@
+use egui::{Id, Ui};
 
+use crate::shortcut::keycodes::SerializableKeycode;
+use crate::utils::{parse_sequence, parse_shortcut, sequence_as_string};
 
 #[cfg(not(target_arch = "wasm32"))]
 pub mod desktop;
@@ -8,4 +11,68 @@ pub use desktop::ShortcutRecorder as PlatformShortcutRecorder;
 #[cfg(target_arch = "wasm32")]
 pub mod web;
 #[cfg(target_arch = "wasm32")]
-pub use web::ShortcutRecorder as PlatformShortcutRecorder;
\ No newline at end of file
+pub use web::ShortcutRecorder as PlatformShortcutRecorder;
+
+/// A button to type the shortcut instead of pressing it, e.g. `Ctrl+Shift+1`,
+/// or `F13, 3` when the recorder takes sequences.
+fn typed_shortcut_ui(
+    ui: &mut Ui,
+    id: usize,
+    shortcut: &mut Option<Vec<SerializableKeycode>>,
+    sequence: Option<&mut Vec<Vec<SerializableKeycode>>>,
+) {
+    let text_id = Id::new(("typed_shortcut", id));
+    let error_id = Id::new(("typed_shortcut_error", id));
+    let typing: Option<String> = ui.data(|data| data.get_temp(text_id));
+
+    let Some(mut text) = typing else {
+        if ui.button("✏").on_hover_text("Type the shortcut").clicked() {
+            let mut steps: Vec<_> = shortcut.iter().cloned().collect();
+            steps.extend(sequence.iter().flat_map(|sequence| sequence.iter().cloned()));
+            ui.data_mut(|data| data.insert_temp(text_id, sequence_as_string(&steps)));
+        }
+        return;
+    };
+
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut text)
+            .hint_text("Ctrl+Shift+1")
+            .desired_width(120.0),
+    );
+    if !response.has_focus() && !response.lost_focus() {
+        response.request_focus();
+    }
+
+    let mut done = false;
+    if response.lost_focus() {
+        if ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+            let parsed = match sequence {
+                Some(sequence) => parse_sequence(&text).map(|mut steps| {
+                    *shortcut = Some(steps.remove(0));
+                    *sequence = steps;
+                }),
+                None => parse_shortcut(&text).map(|chord| *shortcut = Some(chord)),
+            };
+
+            match parsed {
+                Ok(()) => done = true,
+                Err(error) => ui.data_mut(|data| data.insert_temp(error_id, error)),
+            }
+        } else {
+            // Esc o clic altrove: si lascia la scorciatoia com'era
+            done = true;
+        }
+    }
+
+    if done {
+        ui.data_mut(|data| {
+            data.remove::<String>(text_id);
+            data.remove::<String>(error_id);
+        });
+    } else {
+        ui.data_mut(|data| data.insert_temp(text_id, text));
+        if let Some(error) = ui.data(|data| data.get_temp::<String>(error_id)) {
+            ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {error}"));
+        }
+    }
+}
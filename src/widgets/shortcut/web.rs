@@ -1,12 +1,19 @@
-use egui::{Response, Ui, Widget};
+use egui::{Id, Response, Ui, Widget};
+use crate::shortcut::ShortcutBackendKind;
 use crate::shortcut::keycodes::SerializableKeycode;
-use crate::utils::shortcut_as_string;
+use crate::shortcut::chord::recorded_chord;
+use crate::shortcut::layout::KeyboardLayout;
+use crate::shortcut::web::pressed_inputs;
+use crate::utils::{sequence_as_string, shortcut_as_string};
 
 pub struct ShortcutRecorder<'a> {
     pub shortcut: &'a mut Option<Vec<SerializableKeycode>>,
     pub listening_shortcut: &'a mut Option<usize>,
     pub last_pressed_keys: &'a mut Option<Vec<SerializableKeycode>>,
     pub id: usize,
+    pub side_agnostic: bool,
+    pub sequence: Option<&'a mut Vec<Vec<SerializableKeycode>>>,
+    pub layout: Option<&'a KeyboardLayout>,
 }
 
 impl<'a> ShortcutRecorder<'a> {
@@ -21,117 +28,138 @@ impl<'a> ShortcutRecorder<'a> {
             listening_shortcut,
             last_pressed_keys,
             id,
+            side_agnostic: false,
+            sequence: None,
+            layout: None,
+        }
+    }
+
+    /// Lets the user append further steps to the shortcut, for sequences
+    /// such as `F13` then `3`.
+    pub fn sequence(mut self, sequence: &'a mut Vec<Vec<SerializableKeycode>>) -> Self {
+        self.sequence = Some(sequence);
+        self
+    }
+
+    /// The browser only has its own key events: kept for parity with the
+    /// desktop recorder.
+    pub fn backend(self, _backend: ShortcutBackendKind) -> Self {
+        self
+    }
+
+    /// Shows the keys with the labels of the user's keyboard layout.
+    pub fn layout(mut self, layout: &'a KeyboardLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Records Ctrl/Shift/Alt/Super instead of their left or right variant.
+    pub fn side_agnostic(mut self, side_agnostic: bool) -> Self {
+        self.side_agnostic = side_agnostic;
+        self
+    }
+
+    /// The whole recorded shortcut, steps included.
+    fn recorded_label(&self) -> Option<String> {
+        let first = self.shortcut.clone()?;
+        let mut steps = vec![first];
+        steps.extend(self.sequence.iter().flat_map(|sequence| sequence.iter().cloned()));
+        Some(match self.layout {
+            Some(layout) => layout.sequence_label(&steps),
+            None => sequence_as_string(&steps),
+        })
+    }
+
+    fn keys_label(&self, keys: &[SerializableKeycode]) -> String {
+        match self.layout {
+            Some(layout) => layout.shortcut_label(keys),
+            None => shortcut_as_string(keys),
+        }
+    }
+
+    /// Stores a recorded chord as the next step or as a new shortcut.
+    fn store(&mut self, chord: Vec<SerializableKeycode>, appending: bool) {
+        match self.sequence.as_deref_mut() {
+            Some(sequence) if appending => sequence.push(chord),
+            Some(sequence) => {
+                sequence.clear();
+                *self.shortcut = Some(chord);
+            }
+            None => *self.shortcut = Some(chord),
         }
     }
 }
 
 impl Widget for ShortcutRecorder<'_> {
-    fn ui(self, ui: &mut Ui) -> Response {
+    fn ui(mut self, ui: &mut Ui) -> Response {
         let is_listening = self
             .listening_shortcut
             .map_or(false, |active_id| active_id == self.id);
 
+        // Se si sta aggiungendo un passo alla sequenza invece di registrarla da capo
+        let step_id = Id::new(("shortcut_step", self.id));
+        let appending = self.sequence.is_some()
+            && ui.data(|data| data.get_temp::<bool>(step_id)).unwrap_or(false);
+
+        let recorded = self.recorded_label();
+
         let label = if is_listening {
-            if let Some(keys) = &self.last_pressed_keys {
-                if keys.is_empty() {
-                    "Type a Shortcut...".to_string()
-                } else {
-                    shortcut_as_string(keys)
-                }
-            } else {
-                "Type a Shortcut...".to_string()
+            let typing = match &self.last_pressed_keys {
+                Some(keys) if !keys.is_empty() => self.keys_label(keys),
+                _ => "Type a Shortcut...".to_string(),
+            };
+
+            match &recorded {
+                Some(recorded) if appending => format!("{recorded}, {typing}"),
+                _ => typing,
             }
-        } else if let Some(keys) = &self.shortcut {
-            shortcut_as_string(keys)
         } else {
-            "No Shortcut".to_string()
-        }; 
+            recorded.unwrap_or_else(|| "No Shortcut".to_string())
+        };
 
-        let response = ui.button(label);
+        let previous_shortcut = self.shortcut.clone();
+        let previous_sequence = self.sequence.as_deref().cloned();
+        let mut response = ui.button(label);
 
         if response.clicked() {
             *self.listening_shortcut = Some(self.id);
             *self.last_pressed_keys = Some(Vec::new());
+            ui.data_mut(|data| data.insert_temp(step_id, false));
+        }
+
+        if self.shortcut.is_some() && self.sequence.is_some()
+            && ui.button("➕").on_hover_text("Add a step to the sequence").clicked() {
+            *self.listening_shortcut = Some(self.id);
+            *self.last_pressed_keys = Some(Vec::new());
+            ui.data_mut(|data| data.insert_temp(step_id, true));
         }
 
+        super::typed_shortcut_ui(ui, self.id, self.shortcut, self.sequence.as_deref_mut());
+
+        let mut finished = None;
+
         if is_listening {
-            ui.input(|input| {
-                // Ottieni la referenza mutabile a last_pressed_keys, se esiste
-                if let Some(keys) = self.last_pressed_keys.as_mut() {
-                    // Aggiungi modificatori, se premuti e non già presenti
-                    if input.modifiers.ctrl {
-                        let modifier = SerializableKeycode::LControl;
-                        if !keys.contains(&modifier) {
-                            keys.push(modifier);
-                        }
-                    }
-                    else {
-                        if !keys.is_empty() {
-                            *self.shortcut = Some(keys.clone());
-                            *self.listening_shortcut = None;
-                        }
-                    }
-
-                    if input.modifiers.shift {
-                        let modifier = SerializableKeycode::LShift;
-                        if !keys.contains(&modifier) {
-                            keys.push(modifier);
-                        }
-                    }
-                    else {
-                        if !keys.is_empty() {
-                            *self.shortcut = Some(keys.clone());
-                            *self.listening_shortcut = None;
-                        }
-                    }
-
-
-                    if input.modifiers.alt {
-                        let modifier = SerializableKeycode::LAlt;
-                        if !keys.contains(&modifier) {
-                            keys.push(modifier);
-                        }
-                    }
-                    else {
-                        if !keys.is_empty() {
-                            *self.shortcut = Some(keys.clone());
-                            *self.listening_shortcut = None;
-                        }
-                    }
-                    
-                    if input.modifiers.mac_cmd {
-                        let modifier = SerializableKeycode::LMeta;
-                        if !keys.contains(&modifier) {
-                            keys.push(modifier);
-                        }
-                    }
-                    else {
-                        if !keys.is_empty() {
-                            *self.shortcut = Some(keys.clone());
-                            *self.listening_shortcut = None;
-                        }
-                    }
-
-                    // Gestisci gli eventi Key
-                    for event in &input.events {
-                        if let egui::Event::Key { key, pressed, .. } = event {
-                            let key = SerializableKeycode::from(*key);
-                            if *pressed {
-                                if !keys.contains(&key) {
-                                    keys.push(key);
-                                }
-                            } else {
-                                if !keys.is_empty() {
-                                    *self.shortcut = Some(keys.clone());
-                                    *self.listening_shortcut = None;
-                                    *self.last_pressed_keys = None;
-                                    break;
-                                }
-                            }
-                        }
-                    }
+            // Gli stessi `KeyboardEvent.code` del listener delle scorciatoie
+            let current_keys = pressed_inputs();
+
+            match self.last_pressed_keys.clone() {
+                Some(previous_keys) if !previous_keys.is_empty()
+                    && previous_keys.iter().any(|key| !current_keys.contains(key)) =>
+                {
+                    finished = Some(recorded_chord(&previous_keys, self.side_agnostic));
+                    *self.listening_shortcut = None;
+                    *self.last_pressed_keys = None;
                 }
-            });
+                _ => *self.last_pressed_keys = Some(current_keys),
+            }
+        }
+
+        if let Some(chord) = finished {
+            self.store(chord, appending);
+        }
+
+        if *self.shortcut != previous_shortcut || self.sequence.as_deref().cloned() != previous_sequence {
+            response.mark_changed();
         }
 
         response